@@ -0,0 +1,172 @@
+use crate::raytracing::Ray;
+use crate::Vec3;
+use crate::vec::Point3;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Aabb {
+    minimum: Point3,
+    maximum: Point3,
+}
+
+impl Aabb {
+    pub fn new(minimum: Point3, maximum: Point3) -> Aabb {
+        Aabb { minimum, maximum }
+    }
+
+    // Smallest box containing every point. `points` must not be empty.
+    pub fn from_points(points: &[Point3]) -> Aabb {
+        points.iter().skip(1).fold(Aabb::new(points[0], points[0]), |acc, p| {
+            let small = Point3::new(acc.minimum.x.min(p.x), acc.minimum.y.min(p.y), acc.minimum.z.min(p.z));
+            let big = Point3::new(acc.maximum.x.max(p.x), acc.maximum.y.max(p.y), acc.maximum.z.max(p.z));
+            Aabb::new(small, big)
+        })
+    }
+
+    pub fn min(&self) -> Point3 {
+        self.minimum
+    }
+
+    pub fn max(&self) -> Point3 {
+        self.maximum
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    pub fn diagonal(&self) -> Vec3 {
+        self.maximum - self.minimum
+    }
+
+    // Index of the axis along which the box is widest (0 = x, 1 = y, 2 = z).
+    pub fn longest_axis(&self) -> usize {
+        let d = self.diagonal();
+        if d.x > d.y && d.x > d.z {
+            0
+        } else if d.y > d.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn contains(&self, p: Point3) -> bool {
+        (0..3).all(|a| p[a] >= self.minimum[a] && p[a] <= self.maximum[a])
+    }
+
+    // Slab test: clips the ray's parameter interval against each pair of
+    // axis-aligned planes. An axis-parallel ray gives an infinite 1/d, which
+    // yields an unbounded interval when it is inside the slab and an empty one otherwise.
+    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.clip(r, t_min, t_max).is_some()
+    }
+
+    // The part of [t_min, t_max] during which the ray is inside the box.
+    pub fn clip(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let origin = r.origin();
+        let direction = r.direction();
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for a in 0..3 {
+            let inv_d = 1.0 / direction[a];
+            let t0 = (self.minimum[a] - origin[a]) * inv_d;
+            let t1 = (self.maximum[a] - origin[a]) * inv_d;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    // Widens every axis thinner than `delta` so that flat primitives still get a
+    // box the slab test can hit.
+    pub fn pad(&self, delta: f64) -> Aabb {
+        let mut minimum = self.minimum;
+        let mut maximum = self.maximum;
+        let d = self.diagonal();
+        if d.x < delta {
+            minimum.x -= delta / 2.0;
+            maximum.x += delta / 2.0;
+        }
+        if d.y < delta {
+            minimum.y -= delta / 2.0;
+            maximum.y += delta / 2.0;
+        }
+        if d.z < delta {
+            minimum.z -= delta / 2.0;
+            maximum.z += delta / 2.0;
+        }
+        Aabb::new(minimum, maximum)
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.diagonal();
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+}
+
+pub fn surrounding_box(box0: &Aabb, box1: &Aabb) -> Aabb {
+    let small = Point3::new(f64::min(box0.min().x, box1.min().x),
+                            f64::min(box0.min().y, box1.min().y),
+                            f64::min(box0.min().z, box1.min().z));
+    let big = Point3::new(f64::max(box0.max().x, box1.max().x),
+                          f64::max(box0.max().y, box1.max().y),
+                          f64::max(box0.max().z, box1.max().z));
+    Aabb::new(small, big)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::aabb::{Aabb, surrounding_box};
+    use crate::raytracing::Ray;
+    use crate::vec::{Point3, Vec3};
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn check_hit() {
+        let r = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(unit_box().hit(&r, 0.0, f64::INFINITY));
+        assert!(!unit_box().hit(&r, 0.0, 3.0));
+        assert!(!unit_box().hit(&r, 7.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn check_miss_with_axis_parallel_ray() {
+        let r = Ray::new(Point3::new(2.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(!unit_box().hit(&r, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn check_surrounding_box() {
+        let other = Aabb::new(Point3::new(0.0, 2.0, -3.0), Point3::new(4.0, 3.0, 0.0));
+        let bbox = surrounding_box(&unit_box(), &other);
+        assert_eq!(format!("{}", bbox.min()), "-1 -1 -3");
+        assert_eq!(format!("{}", bbox.max()), "4 3 1");
+        assert_eq!(bbox.longest_axis(), 0);
+    }
+
+    #[test]
+    fn check_surface_area_and_centroid() {
+        let bbox = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 2.0, 3.0));
+        assert_approx_eq!(22.0, bbox.surface_area(), 1e-9);
+        assert_eq!(format!("{}", bbox.centroid()), "0.5 1 1.5");
+        assert!(bbox.contains(Point3::new(0.5, 1.0, 2.9)));
+        assert!(!bbox.contains(Point3::new(0.5, 2.5, 1.0)));
+    }
+
+    #[test]
+    fn check_pad() {
+        let flat = Aabb::new(Point3::new(-1.0, 0.0, -1.0), Point3::new(1.0, 0.0, 1.0));
+        let r = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(!flat.hit(&r, 0.0, f64::INFINITY));
+        assert!(flat.pad(0.0001).hit(&r, 0.0, f64::INFINITY));
+        assert_approx_eq!(2.0, flat.pad(0.0001).diagonal().x, 1e-9);
+    }
+}
@@ -0,0 +1,193 @@
+use std::rc::Rc;
+
+use crate::Vec3;
+use crate::aabb::Aabb;
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::vec::Point3;
+
+// Rectangle in the plane z = k, facing +z.
+pub struct XYRect {
+    x0: f64,
+    x1: f64,
+    y0: f64,
+    y1: f64,
+    k: f64,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl XYRect {
+    pub fn new(x0: f64, x1: f64, y0: f64, y1: f64, k: f64, mat_ptr: Rc<dyn Scatter>) -> XYRect {
+        XYRect { x0, x1, y0, y1, k, mat_ptr }
+    }
+}
+
+impl Hittable for XYRect {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = (self.k - r.origin().z) / r.direction().z;
+        if t < t_min || t > t_max || t.is_nan() {
+            return None;
+        }
+        let x = r.origin().x + t * r.direction().x;
+        let y = r.origin().y + t * r.direction().y;
+        if x < self.x0 || x > self.x1 || y < self.y0 || y > self.y1 {
+            return None;
+        }
+        let mut rec = HitRecord::new(r.at(t), Vec3::new(0.0, 0.0, 1.0), Rc::clone(&self.mat_ptr), t);
+        rec.u = (x - self.x0) / (self.x1 - self.x0);
+        rec.v = (y - self.y0) / (self.y1 - self.y0);
+        rec.set_face_normal(r);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(Aabb::new(Point3::new(self.x0, self.y0, self.k), Point3::new(self.x1, self.y1, self.k)).pad(0.0001))
+    }
+}
+
+// Rectangle in the plane y = k, facing +y.
+pub struct XZRect {
+    x0: f64,
+    x1: f64,
+    z0: f64,
+    z1: f64,
+    k: f64,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl XZRect {
+    pub fn new(x0: f64, x1: f64, z0: f64, z1: f64, k: f64, mat_ptr: Rc<dyn Scatter>) -> XZRect {
+        XZRect { x0, x1, z0, z1, k, mat_ptr }
+    }
+}
+
+impl Hittable for XZRect {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = (self.k - r.origin().y) / r.direction().y;
+        if t < t_min || t > t_max || t.is_nan() {
+            return None;
+        }
+        let x = r.origin().x + t * r.direction().x;
+        let z = r.origin().z + t * r.direction().z;
+        if x < self.x0 || x > self.x1 || z < self.z0 || z > self.z1 {
+            return None;
+        }
+        let mut rec = HitRecord::new(r.at(t), Vec3::new(0.0, 1.0, 0.0), Rc::clone(&self.mat_ptr), t);
+        rec.u = (x - self.x0) / (self.x1 - self.x0);
+        rec.v = (z - self.z0) / (self.z1 - self.z0);
+        rec.set_face_normal(r);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(Aabb::new(Point3::new(self.x0, self.k, self.z0), Point3::new(self.x1, self.k, self.z1)).pad(0.0001))
+    }
+}
+
+// Rectangle in the plane x = k, facing +x.
+pub struct YZRect {
+    y0: f64,
+    y1: f64,
+    z0: f64,
+    z1: f64,
+    k: f64,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl YZRect {
+    pub fn new(y0: f64, y1: f64, z0: f64, z1: f64, k: f64, mat_ptr: Rc<dyn Scatter>) -> YZRect {
+        YZRect { y0, y1, z0, z1, k, mat_ptr }
+    }
+}
+
+impl Hittable for YZRect {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = (self.k - r.origin().x) / r.direction().x;
+        if t < t_min || t > t_max || t.is_nan() {
+            return None;
+        }
+        let y = r.origin().y + t * r.direction().y;
+        let z = r.origin().z + t * r.direction().z;
+        if y < self.y0 || y > self.y1 || z < self.z0 || z > self.z1 {
+            return None;
+        }
+        let mut rec = HitRecord::new(r.at(t), Vec3::new(1.0, 0.0, 0.0), Rc::clone(&self.mat_ptr), t);
+        rec.u = (y - self.y0) / (self.y1 - self.y0);
+        rec.v = (z - self.z0) / (self.z1 - self.z0);
+        rec.set_face_normal(r);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(Aabb::new(Point3::new(self.k, self.y0, self.z0), Point3::new(self.k, self.y1, self.z1)).pad(0.0001))
+    }
+}
+
+// Reverses the outward normal of the wrapped object, so the same rectangle type
+// can face either way along its axis.
+pub struct FlipFace {
+    object: Rc<dyn Hittable>,
+}
+
+impl FlipFace {
+    pub fn new(object: Rc<dyn Hittable>) -> FlipFace {
+        FlipFace { object }
+    }
+}
+
+impl Hittable for FlipFace {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut rec = self.object.hit(r, t_min, t_max)?;
+        rec.front_face = !rec.front_face;
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.object.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::aarect::{FlipFace, XZRect};
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    fn floor() -> XZRect {
+        XZRect::new(0.0, 2.0, 0.0, 4.0, 1.0, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_hit() {
+        let r = Ray::new(Point3::new(1.0, 3.0, 1.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = floor().hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(2.0, rec.t, 1e-9);
+        assert_approx_eq!(0.5, rec.u, 1e-9);
+        assert_approx_eq!(0.25, rec.v, 1e-9);
+        assert!(rec.front_face);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-9);
+    }
+
+    #[test]
+    fn check_miss() {
+        let outside = Ray::new(Point3::new(3.0, 3.0, 1.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(floor().hit(&outside, 0.0, f64::INFINITY).is_none());
+        let parallel = Ray::new(Point3::new(1.0, 3.0, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(floor().hit(&parallel, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_flip_face() {
+        let flipped = FlipFace::new(Rc::new(floor()));
+        let r = Ray::new(Point3::new(1.0, -1.0, 1.0), Vec3::new(0.0, 1.0, 0.0));
+        let rec = flipped.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert!(rec.front_face);
+        assert_approx_eq!(-1.0, rec.normal.y, 1e-9);
+    }
+}
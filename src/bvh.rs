@@ -0,0 +1,201 @@
+use std::rc::Rc;
+
+use crate::aabb::{Aabb, surrounding_box};
+use crate::raytracing::{HitRecord, Hittable, HittableList, Ray};
+use crate::vec::Point3;
+
+// Number of centroid buckets evaluated per axis by the surface area heuristic.
+const BUCKET_COUNT: usize = 12;
+
+pub struct BvhNode {
+    left: Rc<dyn Hittable>,
+    right: Rc<dyn Hittable>,
+    bbox: Aabb,
+}
+
+struct Primitive {
+    object: Rc<dyn Hittable>,
+    bbox: Aabb,
+    centroid: Point3,
+}
+
+impl BvhNode {
+    pub fn new(list: &HittableList, time0: f64, time1: f64) -> BvhNode {
+        let mut primitives: Vec<Primitive> = list.objects().iter()
+            .map(|object| {
+                let bbox = object.bounding_box(time0, time1)
+                    .expect("No bounding box in BvhNode constructor.");
+                Primitive { object: Rc::clone(object), bbox, centroid: bbox.centroid() }
+            })
+            .collect();
+        assert!(!primitives.is_empty(), "Cannot build a BvhNode from an empty list.");
+        BvhNode::build(&mut primitives)
+    }
+
+    fn build(primitives: &mut [Primitive]) -> BvhNode {
+        let bbox = primitives.iter()
+            .skip(1)
+            .fold(primitives[0].bbox, |acc, p| surrounding_box(&acc, &p.bbox));
+        if primitives.len() == 1 {
+            let object = Rc::clone(&primitives[0].object);
+            return BvhNode { left: Rc::clone(&object), right: object, bbox };
+        }
+        let mid = split(primitives);
+        let (left, right) = primitives.split_at_mut(mid);
+        BvhNode { left: child(left), right: child(right), bbox }
+    }
+}
+
+fn child(primitives: &mut [Primitive]) -> Rc<dyn Hittable> {
+    if primitives.len() == 1 {
+        Rc::clone(&primitives[0].object)
+    } else {
+        Rc::new(BvhNode::build(primitives))
+    }
+}
+
+fn merge(a: Option<Aabb>, b: &Aabb) -> Option<Aabb> {
+    match a {
+        Some(a) => Some(surrounding_box(&a, b)),
+        None => Some(*b),
+    }
+}
+
+fn bucket_index(centroid: f64, min: f64, extent: f64) -> usize {
+    let b = ((centroid - min) / extent * BUCKET_COUNT as f64) as usize;
+    b.min(BUCKET_COUNT - 1)
+}
+
+// Reorders `primitives` so that the split chosen by the surface area heuristic
+// separates them into two non-empty halves and returns the index of the split.
+fn split(primitives: &mut [Primitive]) -> usize {
+    let mut c_min = primitives[0].centroid;
+    let mut c_max = primitives[0].centroid;
+    for p in primitives.iter() {
+        c_min = Point3::new(c_min.x.min(p.centroid.x), c_min.y.min(p.centroid.y), c_min.z.min(p.centroid.z));
+        c_max = Point3::new(c_max.x.max(p.centroid.x), c_max.y.max(p.centroid.y), c_max.z.max(p.centroid.z));
+    }
+
+    let mut best: Option<(f64, usize, usize)> = None; // (cost, axis, last bucket of the left half)
+    for axis in 0..3 {
+        let extent = c_max[axis] - c_min[axis];
+        if extent <= 0.0 {
+            continue;
+        }
+        let mut counts = [0usize; BUCKET_COUNT];
+        let mut boxes: [Option<Aabb>; BUCKET_COUNT] = [None; BUCKET_COUNT];
+        for p in primitives.iter() {
+            let b = bucket_index(p.centroid[axis], c_min[axis], extent);
+            counts[b] += 1;
+            boxes[b] = merge(boxes[b], &p.bbox);
+        }
+
+        // Sweep from the right to get the area and count of every right half.
+        let mut right_area = [0.0; BUCKET_COUNT];
+        let mut right_count = [0usize; BUCKET_COUNT];
+        let mut acc_box: Option<Aabb> = None;
+        let mut acc_count = 0;
+        for b in (1..BUCKET_COUNT).rev() {
+            if let Some(bbox) = &boxes[b] {
+                acc_box = merge(acc_box, bbox);
+            }
+            acc_count += counts[b];
+            right_area[b] = acc_box.map_or(0.0, |bbox| bbox.surface_area());
+            right_count[b] = acc_count;
+        }
+
+        let mut acc_box: Option<Aabb> = None;
+        let mut acc_count = 0;
+        for b in 0..BUCKET_COUNT - 1 {
+            if let Some(bbox) = &boxes[b] {
+                acc_box = merge(acc_box, bbox);
+            }
+            acc_count += counts[b];
+            if acc_count == 0 || right_count[b + 1] == 0 {
+                continue;
+            }
+            let left_area = acc_box.map_or(0.0, |bbox| bbox.surface_area());
+            let cost = left_area * acc_count as f64 + right_area[b + 1] * right_count[b + 1] as f64;
+            if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                best = Some((cost, axis, b));
+            }
+        }
+    }
+
+    match best {
+        Some((_, axis, bucket)) => {
+            let extent = c_max[axis] - c_min[axis];
+            let mut mid = 0;
+            for i in 0..primitives.len() {
+                if bucket_index(primitives[i].centroid[axis], c_min[axis], extent) <= bucket {
+                    primitives.swap(i, mid);
+                    mid += 1;
+                }
+            }
+            mid
+        }
+        // All centroids coincide, so any split is as good as another.
+        None => primitives.len() / 2,
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return None;
+        }
+        let hit_left = self.left.hit(r, t_min, t_max);
+        let closest_so_far = hit_left.as_ref().map_or(t_max, |rec| rec.t);
+        let hit_right = self.right.hit(r, t_min, closest_so_far);
+        hit_right.or(hit_left)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::bvh::BvhNode;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, HittableList, Ray, Sphere};
+    use crate::vec::{Point3, Vec3};
+
+    #[test]
+    fn check_bvh_matches_list() {
+        let material = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HittableList::new();
+        for _ in 0..200 {
+            let center = Point3::random_in_range(-10.0, 10.0);
+            list.add(Rc::new(Sphere::new(center, 0.5, material.clone())));
+        }
+        let bvh = BvhNode::new(&list, 0.0, 1.0);
+
+        for _ in 0..500 {
+            let r = Ray::new(Point3::random_in_range(-15.0, 15.0), Vec3::random_in_unit_sphere());
+            let expected = list.hit(&r, 0.001, f64::INFINITY);
+            let actual = bvh.hit(&r, 0.001, f64::INFINITY);
+            assert_eq!(expected.is_some(), actual.is_some());
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                assert_approx_eq!(expected.t, actual.t, 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn check_bvh_single_object() {
+        let material = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HittableList::new();
+        list.add(Rc::new(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material)));
+        let bvh = BvhNode::new(&list, 0.0, 1.0);
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = bvh.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(0.5, rec.t, 1e-9);
+    }
+}
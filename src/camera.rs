@@ -1,5 +1,5 @@
 use crate::{vec, Vec3};
-use crate::raytracing::{degrees_to_radians, Ray};
+use crate::raytracing::{degrees_to_radians, random_double_range, Ray};
 use crate::vec::Point3;
 
 pub struct Camera {
@@ -8,9 +8,12 @@ pub struct Camera {
     vertical: Vec3,
     lower_left_corner: Vec3,
     lens_radius: f64,
+    #[allow(dead_code)]
     w: Vec3,
     u: Vec3,
     v: Vec3,
+    time0: f64, // shutter open
+    time1: f64, // shutter close
 }
 
 impl Camera {
@@ -45,11 +48,25 @@ impl Camera {
             w,
             u,
             v,
+            time0: 0.0,
+            time1: 0.0,
         }
     }
+    // Keeps the shutter open from time0 to time1; rays are spread uniformly over
+    // the interval, which blurs anything moving during it.
+    pub fn with_shutter(mut self, time0: f64, time1: f64) -> Camera {
+        self.time0 = time0;
+        self.time1 = time1;
+        self
+    }
     pub fn get_ray(&self, u: f64, v: f64) -> Ray {
         let rd = self.lens_radius * vec::random_in_unit_disk();
         let offset = self.u * rd.x + self.v * rd.y;
-        Ray::new(self.origin + offset, self.lower_left_corner + u * self.horizontal + v * self.vertical - self.origin - offset)
+        let time = if self.time1 > self.time0 {
+            random_double_range(self.time0, self.time1)
+        } else {
+            self.time0
+        };
+        Ray::with_time(self.origin + offset, self.lower_left_corner + u * self.horizontal + v * self.vertical - self.origin - offset, time)
     }
 }
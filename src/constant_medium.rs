@@ -0,0 +1,105 @@
+use std::rc::Rc;
+
+use crate::Vec3;
+use crate::aabb::Aabb;
+use crate::color::Color;
+use crate::material::{Isotropic, Scatter};
+use crate::raytracing::{HitRecord, Hittable, random_double, Ray};
+
+// Fog or smoke of uniform density filling a closed boundary. A ray crossing the
+// volume scatters after an exponentially distributed distance, so thicker or
+// denser volumes are more likely to stop it.
+pub struct ConstantMedium {
+    boundary: Rc<dyn Hittable>,
+    phase_function: Rc<dyn Scatter>,
+    neg_inv_density: f64,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Rc<dyn Hittable>, density: f64, albedo: Color) -> ConstantMedium {
+        ConstantMedium::with_phase_function(boundary, density, Rc::new(Isotropic::new(albedo)))
+    }
+
+    pub fn with_phase_function(boundary: Rc<dyn Hittable>, density: f64, phase_function: Rc<dyn Scatter>) -> ConstantMedium {
+        ConstantMedium { boundary, phase_function, neg_inv_density: -1.0 / density }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Entry and exit of the boundary along the whole line, so rays starting
+        // inside the volume are handled too. Assumes a convex boundary.
+        let entry = self.boundary.hit(r, f64::NEG_INFINITY, f64::INFINITY)?;
+        let exit = self.boundary.hit(r, entry.t + 0.0001, f64::INFINITY)?;
+
+        let t_enter = entry.t.max(t_min).max(0.0);
+        let t_exit = exit.t.min(t_max);
+        if t_enter >= t_exit {
+            return None;
+        }
+
+        let ray_length = r.direction().length();
+        let distance_inside_boundary = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * random_double().ln();
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+        // Normal and face are meaningless inside a volume; the phase function ignores them.
+        let mut rec = HitRecord::new(r.at(t), Vec3::new(1.0, 0.0, 0.0), Rc::clone(&self.phase_function), t);
+        rec.front_face = true;
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.boundary.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::color::Color;
+    use crate::constant_medium::ConstantMedium;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, Ray, Sphere};
+    use crate::vec::{Point3, Vec3};
+
+    fn fog(density: f64) -> ConstantMedium {
+        let boundary = Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0,
+                                           Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))));
+        ConstantMedium::new(boundary, density, Color::new(0.9, 0.9, 0.9))
+    }
+
+    #[test]
+    fn check_hits_stay_inside_boundary() {
+        let medium = fog(2.0);
+        let r = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        for _ in 0..100 {
+            if let Some(rec) = medium.hit(&r, 0.001, f64::INFINITY) {
+                assert!(rec.t >= 4.0 && rec.t <= 6.0);
+            }
+        }
+        let miss = Ray::new(Point3::new(0.0, 2.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(medium.hit(&miss, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_density_controls_scattering() {
+        let r = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let count = |medium: &ConstantMedium| (0..1000).filter(|_| medium.hit(&r, 0.001, f64::INFINITY).is_some()).count();
+        // Probability of scattering over a path of length 2 is 1 - exp(-2 * density).
+        assert!(count(&fog(100.0)) > 990);
+        assert!(count(&fog(0.01)) < 100);
+    }
+
+    #[test]
+    fn check_ray_starting_inside() {
+        let medium = fog(1000.0);
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = medium.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!(rec.t < 0.1);
+    }
+}
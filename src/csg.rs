@@ -0,0 +1,206 @@
+use std::rc::Rc;
+
+use crate::aabb::{Aabb, surrounding_box};
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::vec::Point3;
+
+// Upper bound on the surfaces collected per child, as a guard against objects
+// that keep reporting hits at the same spot.
+const MAX_CROSSINGS: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOp::Union => in_left || in_right,
+            CsgOp::Intersection => in_left && in_right,
+            CsgOp::Difference => in_left && !in_right,
+        }
+    }
+}
+
+// Boolean combination of two closed objects. Both children are traced along the
+// whole line of the ray to find where it enters and leaves each of them; the
+// result's surface is wherever the combined inside/outside state changes.
+pub struct Csg {
+    op: CsgOp,
+    left: Rc<dyn Hittable>,
+    right: Rc<dyn Hittable>,
+}
+
+impl Csg {
+    pub fn new(op: CsgOp, left: Rc<dyn Hittable>, right: Rc<dyn Hittable>) -> Csg {
+        Csg { op, left, right }
+    }
+
+    pub fn union(left: Rc<dyn Hittable>, right: Rc<dyn Hittable>) -> Csg {
+        Csg::new(CsgOp::Union, left, right)
+    }
+
+    pub fn intersection(left: Rc<dyn Hittable>, right: Rc<dyn Hittable>) -> Csg {
+        Csg::new(CsgOp::Intersection, left, right)
+    }
+
+    // `left` with the volume of `right` cut away.
+    pub fn difference(left: Rc<dyn Hittable>, right: Rc<dyn Hittable>) -> Csg {
+        Csg::new(CsgOp::Difference, left, right)
+    }
+}
+
+// Every surface crossing of `object` along the ray, in order. A front-face hit
+// enters the object and a back-face hit leaves it.
+fn crossings(object: &dyn Hittable, r: &Ray) -> Vec<HitRecord> {
+    let mut hits = Vec::new();
+    let mut t = f64::NEG_INFINITY;
+    while hits.len() < MAX_CROSSINGS {
+        match object.hit(r, t, f64::INFINITY) {
+            Some(rec) => {
+                t = rec.t + 0.0001;
+                hits.push(rec);
+            }
+            None => break,
+        }
+    }
+    hits
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let left = crossings(self.left.as_ref(), r);
+        let right = crossings(self.right.as_ref(), r);
+        // If the first crossing leaves the object, the ray started inside it.
+        let mut in_left = left.first().is_some_and(|rec| !rec.front_face);
+        let mut in_right = right.first().is_some_and(|rec| !rec.front_face);
+
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+        loop {
+            let take_left = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => l.t <= r.t,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => return None,
+            };
+            let was_inside = self.op.inside(in_left, in_right);
+            let mut rec = if take_left {
+                in_left = !in_left;
+                left.next()?
+            } else {
+                in_right = !in_right;
+                right.next()?
+            };
+            let is_inside = self.op.inside(in_left, in_right);
+            if rec.t > t_max {
+                return None;
+            }
+            if was_inside != is_inside && rec.t >= t_min {
+                // The normal already faces the ray; only the side it was hit
+                // from can change, e.g. the inside of a cut-away surface.
+                rec.front_face = is_inside;
+                return Some(rec);
+            }
+        }
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        match self.op {
+            CsgOp::Union => {
+                let left = self.left.bounding_box(time0, time1)?;
+                let right = self.right.bounding_box(time0, time1)?;
+                Some(surrounding_box(&left, &right))
+            }
+            CsgOp::Intersection => {
+                match (self.left.bounding_box(time0, time1), self.right.bounding_box(time0, time1)) {
+                    (Some(left), Some(right)) => {
+                        let small = Point3::new(left.min().x.max(right.min().x),
+                                                left.min().y.max(right.min().y),
+                                                left.min().z.max(right.min().z));
+                        let big = Point3::new(left.max().x.min(right.max().x),
+                                              left.max().y.min(right.max().y),
+                                              left.max().z.min(right.max().z));
+                        Some(Aabb::new(small, big))
+                    }
+                    (left, right) => left.or(right),
+                }
+            }
+            CsgOp::Difference => self.left.bounding_box(time0, time1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::csg::Csg;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, Ray, Sphere};
+    use crate::vec::{Point3, Vec3};
+
+    fn sphere(x: f64) -> Rc<Sphere> {
+        Rc::new(Sphere::new(Point3::new(x, 0.0, 0.0), 1.0, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))))
+    }
+
+    fn along_x() -> Ray {
+        Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn check_union() {
+        let union = Csg::union(sphere(0.0), sphere(1.0));
+        let rec = union.hit(&along_x(), 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.0, rec.t, 1e-9);
+        assert!(rec.front_face);
+        // The inner surfaces are swallowed: the next crossing leaves the union at x = 2.
+        let rec = union.hit(&along_x(), 4.5, f64::INFINITY).unwrap();
+        assert_approx_eq!(7.0, rec.t, 1e-9);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn check_intersection_lens() {
+        let lens = Csg::intersection(sphere(0.0), sphere(1.0));
+        let rec = lens.hit(&along_x(), 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(5.0, rec.t, 1e-9);
+        assert!(rec.front_face);
+        assert_approx_eq!(-1.0, rec.normal.x, 1e-9);
+        let rec = lens.hit(&along_x(), 5.5, f64::INFINITY).unwrap();
+        assert_approx_eq!(6.0, rec.t, 1e-9);
+        assert!(!rec.front_face);
+
+        let bbox = lens.bounding_box(0.0, 1.0).unwrap();
+        assert_approx_eq!(0.0, bbox.min().x, 1e-9);
+        assert_approx_eq!(1.0, bbox.max().x, 1e-9);
+    }
+
+    #[test]
+    fn check_difference() {
+        let bitten = Csg::difference(sphere(0.0), sphere(1.0));
+        let rec = bitten.hit(&along_x(), 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.0, rec.t, 1e-9);
+        // Leaving through the cut, which is the inside of the right sphere.
+        let rec = bitten.hit(&along_x(), 4.5, f64::INFINITY).unwrap();
+        assert_approx_eq!(5.0, rec.t, 1e-9);
+        assert!(!rec.front_face);
+        assert_approx_eq!(-1.0, rec.normal.x, 1e-9);
+        assert!(bitten.hit(&along_x(), 5.5, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_ray_starting_inside() {
+        let union = Csg::union(sphere(0.0), sphere(1.0));
+        let r = Ray::new(Point3::new(0.5, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = union.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_approx_eq!(1.5, rec.t, 1e-9);
+        assert!(!rec.front_face);
+    }
+}
@@ -0,0 +1,75 @@
+use std::rc::Rc;
+
+use crate::aabb::Aabb;
+use crate::aarect::{FlipFace, XYRect, XZRect, YZRect};
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, HittableList, Ray};
+use crate::vec::Point3;
+
+// Axis-aligned box made of six rectangles whose normals all point outwards.
+// Named `Cuboid` rather than `Box` to avoid shadowing `std::boxed::Box`.
+pub struct Cuboid {
+    box_min: Point3,
+    box_max: Point3,
+    sides: HittableList,
+}
+
+impl Cuboid {
+    pub fn new(p0: Point3, p1: Point3, mat_ptr: Rc<dyn Scatter>) -> Cuboid {
+        let mut sides = HittableList::with_capacity(6);
+
+        sides.add(Rc::new(XYRect::new(p0.x, p1.x, p0.y, p1.y, p1.z, mat_ptr.clone())));
+        sides.add(Rc::new(FlipFace::new(Rc::new(XYRect::new(p0.x, p1.x, p0.y, p1.y, p0.z, mat_ptr.clone())))));
+
+        sides.add(Rc::new(XZRect::new(p0.x, p1.x, p0.z, p1.z, p1.y, mat_ptr.clone())));
+        sides.add(Rc::new(FlipFace::new(Rc::new(XZRect::new(p0.x, p1.x, p0.z, p1.z, p0.y, mat_ptr.clone())))));
+
+        sides.add(Rc::new(YZRect::new(p0.y, p1.y, p0.z, p1.z, p1.x, mat_ptr.clone())));
+        sides.add(Rc::new(FlipFace::new(Rc::new(YZRect::new(p0.y, p1.y, p0.z, p1.z, p0.x, mat_ptr)))));
+
+        Cuboid { box_min: p0, box_max: p1, sides }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.sides.hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(Aabb::new(self.box_min, self.box_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::cuboid::Cuboid;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    #[test]
+    fn check_outward_normals() {
+        let cuboid = Cuboid::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0),
+                                 Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let directions = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)];
+        for d in directions {
+            for sign in [1.0, -1.0] {
+                let r = Ray::new(Point3::new(0.5, 0.5, 0.5) - 2.0 * sign * d, sign * d);
+                let rec = cuboid.hit(&r, 0.0, f64::INFINITY).unwrap();
+                assert!(rec.front_face);
+                assert_approx_eq!(1.5, rec.t, 1e-9);
+
+                let inside = Ray::new(Point3::new(0.5, 0.5, 0.5), sign * d);
+                let rec = cuboid.hit(&inside, 0.0, f64::INFINITY).unwrap();
+                assert!(!rec.front_face);
+                assert_approx_eq!(0.5, rec.t, 1e-9);
+            }
+        }
+    }
+}
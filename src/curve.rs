@@ -0,0 +1,274 @@
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::Aabb;
+use crate::bvh::BvhNode;
+use crate::frame::{AxisFrame, azimuth, hit_frustum_side};
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, HittableList, Ray};
+use crate::roots::solve_quadratic;
+use crate::vec::Point3;
+
+// Straight pieces each curve is split into for intersection. Strands are thin,
+// so the chords stay well within a pixel for typical hair and grass.
+const SEGMENTS: usize = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CurveMode {
+    // Flat strip that always turns to face the ray, for very thin strands.
+    Ribbon,
+    // Round tube, for strands seen up close.
+    Cylinder,
+}
+
+struct CurveData {
+    control_points: [Point3; 4],
+    width0: f64,
+    width1: f64,
+    mode: CurveMode,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl CurveData {
+    fn point(&self, s: f64) -> Point3 {
+        let [p0, p1, p2, p3] = self.control_points;
+        let r = 1.0 - s;
+        r * r * r * p0 + 3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s * p3
+    }
+
+    fn tangent(&self, s: f64) -> Vec3 {
+        let [p0, p1, p2, p3] = self.control_points;
+        let r = 1.0 - s;
+        3.0 * r * r * (p1 - p0) + 6.0 * r * s * (p2 - p1) + 3.0 * s * s * (p3 - p2)
+    }
+
+    fn width(&self, s: f64) -> f64 {
+        self.width0 + s * (self.width1 - self.width0)
+    }
+
+    // Component of `v` perpendicular to the curve at `s`.
+    fn perpendicular(&self, s: f64, v: Vec3) -> Vec3 {
+        let tangent = vec::unit_vector(self.tangent(s));
+        v - vec::dot(&v, &tangent) * tangent
+    }
+}
+
+// Cubic Bézier strand whose width tapers linearly from `width0` to `width1`.
+// Hits report the curve parameter in `v`, and `u` runs across (ribbon) or
+// around (cylinder) the strand. A curve collapsed to a point has no segments
+// and is never hit, but keeps a tiny box at that point so a BVH can hold it.
+pub struct Curve {
+    segments: Option<BvhNode>,
+    start: Point3,
+}
+
+impl Curve {
+    pub fn new(control_points: [Point3; 4], width0: f64, width1: f64, mode: CurveMode, mat_ptr: Rc<dyn Scatter>) -> Curve {
+        let curve = Rc::new(CurveData { control_points, width0, width1, mode, mat_ptr });
+        let mut segments = HittableList::with_capacity(SEGMENTS);
+        for i in 0..SEGMENTS {
+            let s0 = i as f64 / SEGMENTS as f64;
+            let s1 = (i + 1) as f64 / SEGMENTS as f64;
+            let (a, b) = (curve.point(s0), curve.point(s1));
+            if (b - a).near_zero() {
+                continue;
+            }
+            segments.add(Rc::new(CurveSegment { curve: Rc::clone(&curve), s0, s1, frame: AxisFrame::new(a, b) }));
+        }
+        let segments = if segments.objects().is_empty() { None } else { Some(BvhNode::new(&segments, 0.0, 1.0)) };
+        Curve { segments, start: control_points[0] }
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.segments.as_ref()?.hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        match &self.segments {
+            Some(segments) => segments.bounding_box(time0, time1),
+            None => Some(Aabb::new(self.start, self.start).pad(0.0001)),
+        }
+    }
+}
+
+// Chord of the curve between parameters s0 and s1.
+struct CurveSegment {
+    curve: Rc<CurveData>,
+    s0: f64,
+    s1: f64,
+    frame: AxisFrame,
+}
+
+impl CurveSegment {
+    // Hit with the tapered tube around the chord, shaded with the normal of the
+    // true curve at the matching parameter. A ball at the start of every inner
+    // segment fills the gap the chords leave on the outside of a bend.
+    fn hit_cylinder(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let curve = &self.curve;
+        let (o, d) = self.frame.local_ray(r);
+        let r0 = 0.5 * curve.width(self.s0);
+        let tube = hit_frustum_side(o, d, r0, 0.5 * curve.width(self.s1), self.frame.height, t_min, t_max)
+            .map(|hit| (hit.t, self.s0 + hit.v * (self.s1 - self.s0)));
+        let joint = if self.s0 > 0.0 {
+            solve_quadratic(d.length_squared(), 2.0 * vec::dot(&o, &d), o.length_squared() - r0 * r0)
+                .and_then(|(t0, t1)| [t0, t1].into_iter().find(|t| *t >= t_min && *t <= t_max))
+                .map(|t| (t, self.s0))
+        } else {
+            None
+        };
+        let (t, s) = match (tube, joint) {
+            (Some(tube), Some(joint)) => if joint.0 < tube.0 { joint } else { tube },
+            (tube, joint) => tube.or(joint)?,
+        };
+
+        let p = r.at(t);
+        let outward_normal = vec::unit_vector(curve.perpendicular(s, p - curve.point(s)));
+        let mut rec = HitRecord::new(p, outward_normal, Rc::clone(&curve.mat_ptr), t);
+        rec.set_face_normal(r);
+        rec.u = azimuth(o + t * d);
+        rec.v = s;
+        Some(rec)
+    }
+
+    // Hit where the ray passes closest to the chord, if that is within half the
+    // width. The ribbon is oriented to face the ray, so it is always hit head on.
+    fn hit_ribbon(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let curve = &self.curve;
+        let (o, d) = (r.origin(), r.direction());
+        let a = self.frame.base;
+        let w = self.frame.height * self.frame.axis;
+        let offset = o - a;
+        let (dd, dw, ww) = (vec::dot(&d, &d), vec::dot(&d, &w), vec::dot(&w, &w));
+        let (dr, wr) = (vec::dot(&d, &offset), vec::dot(&w, &offset));
+        let det = dw * dw - dd * ww;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let along = ((dw * dr - dd * wr) / det).clamp(0.0, 1.0);
+        let t = vec::dot(&d, &(a + along * w - o)) / dd;
+        if t < t_min || t > t_max {
+            return None;
+        }
+        let s = self.s0 + along * (self.s1 - self.s0);
+        let p = r.at(t);
+        let half_width = 0.5 * curve.width(s);
+        let across = p - curve.point(s);
+        if (p - (a + along * w)).length() > half_width {
+            return None;
+        }
+
+        let normal = vec::unit_vector(curve.perpendicular(s, -d));
+        let side = vec::unit_vector(vec::cross(&curve.tangent(s), &normal));
+        let mut rec = HitRecord::new(p, normal, Rc::clone(&curve.mat_ptr), t);
+        rec.set_face_normal(r);
+        rec.u = (0.5 + 0.5 * vec::dot(&across, &side) / half_width).clamp(0.0, 1.0);
+        rec.v = s;
+        Some(rec)
+    }
+}
+
+impl Hittable for CurveSegment {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        match self.curve.mode {
+            CurveMode::Ribbon => self.hit_ribbon(r, t_min, t_max),
+            CurveMode::Cylinder => self.hit_cylinder(r, t_min, t_max),
+        }
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        let half_width = 0.5 * self.curve.width(self.s0).max(self.curve.width(self.s1));
+        let pad = Vec3::new(half_width, half_width, half_width);
+        let a = self.frame.base;
+        let b = a + self.frame.height * self.frame.axis;
+        let bbox = Aabb::from_points(&[a, b]);
+        Some(Aabb::new(bbox.min() - pad, bbox.max() + pad))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::bvh::BvhNode;
+    use crate::curve::{Curve, CurveMode};
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, HittableList, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    // Straight strand along x from 0 to 3, tapering from 0.4 to 0.2 wide.
+    fn straight(mode: CurveMode) -> Curve {
+        Curve::new([Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0),
+                    Point3::new(2.0, 0.0, 0.0), Point3::new(3.0, 0.0, 0.0)],
+                   0.4, 0.2, mode, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_cylinder_taper() {
+        let strand = straight(CurveMode::Cylinder);
+        let r = Ray::new(Point3::new(1.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = strand.hit(&r, 0.0, f64::INFINITY).unwrap();
+        // Half way along the width is 0.3.
+        assert_approx_eq!(5.0 - 0.15, rec.t, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-9);
+        assert_approx_eq!(0.5, rec.v, 1e-9);
+        assert!(rec.front_face);
+
+        let past_end = Ray::new(Point3::new(3.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(strand.hit(&past_end, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_ribbon_faces_ray() {
+        let strand = straight(CurveMode::Ribbon);
+        let d = Vec3::new(0.0, -1.0, -1.0);
+        let r = Ray::new(Point3::new(0.75, 5.0, 5.1), d);
+        let rec = strand.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(0.25, rec.v, 1e-9);
+        assert_approx_eq!(std::f64::consts::FRAC_1_SQRT_2, rec.normal.y, 1e-9);
+        assert_approx_eq!(std::f64::consts::FRAC_1_SQRT_2, rec.normal.z, 1e-9);
+        assert!(rec.front_face);
+        assert!(rec.u != 0.5);
+
+        // Width there is 0.35, so a ray passing 0.3 / sqrt(2) away misses.
+        let wide = Ray::new(Point3::new(0.75, 5.0, 5.3), d);
+        assert!(strand.hit(&wide, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_normal_follows_curve() {
+        // Arch whose apex at s = 0.5 is (0, 1.5, 0).
+        let arch = Curve::new([Point3::new(-1.0, 0.0, 0.0), Point3::new(-1.0, 2.0, 0.0),
+                               Point3::new(1.0, 2.0, 0.0), Point3::new(1.0, 0.0, 0.0)],
+                              0.1, 0.1, CurveMode::Cylinder, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let r = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = arch.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(5.0 - 1.55, rec.t, 1e-2);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-3);
+        assert_approx_eq!(0.5, rec.v, 1e-3);
+
+        // The legs flare out toward the feet, so the normal there tilts up.
+        let side = Ray::new(Point3::new(5.0, 0.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let rec = arch.hit(&side, 0.0, f64::INFINITY).unwrap();
+        assert!(rec.normal.x > 0.9 && rec.normal.y > 0.0);
+    }
+
+    #[test]
+    fn check_degenerate_curve_is_empty() {
+        let p = Point3::new(1.0, 2.0, 3.0);
+        let dot = Curve::new([p; 4], 0.1, 0.1, CurveMode::Cylinder, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let r = Ray::new(Point3::new(1.0, 2.0, 10.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(dot.hit(&r, 0.0, f64::INFINITY).is_none());
+        let bbox = dot.bounding_box(0.0, 1.0).unwrap();
+        assert!(bbox.min().x < 1.0 && bbox.max().x > 1.0);
+        // It can sit in a BVH next to other geometry.
+        let mut world = HittableList::new();
+        world.add(Rc::new(dot));
+        world.add(Rc::new(straight(CurveMode::Cylinder)));
+        assert!(BvhNode::new(&world, 0.0, 1.0).hit(&r, 0.0, f64::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::{Aabb, surrounding_box};
+use crate::frame::{AxisFrame, azimuth, hit_frustum_side, SurfaceHit};
+use crate::material::Scatter;
+use crate::plane::disk_box;
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::roots::solve_quadratic;
+use crate::vec::Point3;
+
+fn closest(a: Option<SurfaceHit>, b: Option<SurfaceHit>) -> Option<SurfaceHit> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b.t < a.t { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+// Disk of `radius` in the plane z = `z`, facing along `normal_z`.
+fn hit_cap(o: Point3, d: Vec3, z: f64, radius: f64, normal_z: f64, t_min: f64, t_max: f64) -> Option<SurfaceHit> {
+    if d.z.abs() < 1e-12 {
+        return None;
+    }
+    let t = (z - o.z) / d.z;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    let p = o + t * d;
+    let distance_squared = p.x * p.x + p.y * p.y;
+    if distance_squared > radius * radius {
+        return None;
+    }
+    Some(SurfaceHit { t, normal: Vec3::new(0.0, 0.0, normal_z), u: azimuth(p), v: distance_squared.sqrt() / radius })
+}
+
+fn hit_frustum(frame: &AxisFrame, r: &Ray, r0: f64, r1: f64, capped: bool, t_min: f64, t_max: f64) -> Option<SurfaceHit> {
+    let (o, d) = frame.local_ray(r);
+    let mut hit = hit_frustum_side(o, d, r0, r1, frame.height, t_min, t_max);
+    if capped {
+        hit = closest(hit, hit_cap(o, d, 0.0, r0, -1.0, t_min, t_max));
+        hit = closest(hit, hit_cap(o, d, frame.height, r1, 1.0, t_min, t_max));
+    }
+    hit
+}
+
+// Finite cylinder between the centers of its two end faces. An uncapped
+// cylinder is an open tube whose inside is visible.
+pub struct Cylinder {
+    frame: AxisFrame,
+    radius: f64,
+    capped: bool,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Cylinder {
+    pub fn new(p0: Point3, p1: Point3, radius: f64, capped: bool, mat_ptr: Rc<dyn Scatter>) -> Cylinder {
+        Cylinder { frame: AxisFrame::new(p0, p1), radius, capped, mat_ptr }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let hit = hit_frustum(&self.frame, r, self.radius, self.radius, self.capped, t_min, t_max)?;
+        Some(self.frame.record(r, hit, &self.mat_ptr))
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        let top = self.frame.base + self.frame.height * self.frame.axis;
+        Some(surrounding_box(&disk_box(self.frame.base, self.frame.axis, self.radius),
+                             &disk_box(top, self.frame.axis, self.radius)))
+    }
+}
+
+// Truncated cone with radius `r0` at `p0` and `r1` at `p1`; a zero radius at
+// either end gives a pointed cone.
+pub struct Cone {
+    frame: AxisFrame,
+    r0: f64,
+    r1: f64,
+    capped: bool,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Cone {
+    pub fn new(p0: Point3, r0: f64, p1: Point3, r1: f64, capped: bool, mat_ptr: Rc<dyn Scatter>) -> Cone {
+        assert!(r0 >= 0.0 && r1 >= 0.0 && r0 + r1 > 0.0, "Cone needs non-negative radii, not both zero.");
+        Cone { frame: AxisFrame::new(p0, p1), r0, r1, capped, mat_ptr }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let hit = hit_frustum(&self.frame, r, self.r0, self.r1, self.capped, t_min, t_max)?;
+        Some(self.frame.record(r, hit, &self.mat_ptr))
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        let top = self.frame.base + self.frame.height * self.frame.axis;
+        Some(surrounding_box(&disk_box(self.frame.base, self.frame.axis, self.r0),
+                             &disk_box(top, self.frame.axis, self.r1)))
+    }
+}
+
+// Cylinder between `p0` and `p1` closed by hemispheres of the same radius.
+pub struct Capsule {
+    frame: AxisFrame,
+    radius: f64,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Capsule {
+    pub fn new(p0: Point3, p1: Point3, radius: f64, mat_ptr: Rc<dyn Scatter>) -> Capsule {
+        Capsule { frame: AxisFrame::new(p0, p1), radius, mat_ptr }
+    }
+
+    // Hemisphere around `center_z` on the axis; `side` selects the half beyond
+    // the end of the cylinder (-1 below the base, 1 above the top).
+    fn hit_hemisphere(&self, o: Point3, d: Vec3, center_z: f64, side: f64, t_min: f64, t_max: f64) -> Option<SurfaceHit> {
+        let oc = o - Vec3::new(0.0, 0.0, center_z);
+        let (t0, t1) = solve_quadratic(d.length_squared(), 2.0 * vec::dot(&oc, &d),
+                                       oc.length_squared() - self.radius * self.radius)?;
+        let total = self.frame.height + 2.0 * self.radius;
+        for t in [t0, t1] {
+            if t < t_min || t > t_max {
+                continue;
+            }
+            let p = o + t * d;
+            if (p.z - center_z) * side < 0.0 {
+                continue;
+            }
+            let normal = (p - Vec3::new(0.0, 0.0, center_z)) / self.radius;
+            return Some(SurfaceHit { t, normal, u: azimuth(p), v: (p.z + self.radius) / total });
+        }
+        None
+    }
+}
+
+impl Hittable for Capsule {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (o, d) = self.frame.local_ray(r);
+        let height = self.frame.height;
+        let mut hit = hit_frustum_side(o, d, self.radius, self.radius, height, t_min, t_max).map(|mut hit| {
+            hit.v = (hit.v * height + self.radius) / (height + 2.0 * self.radius);
+            hit
+        });
+        hit = closest(hit, self.hit_hemisphere(o, d, 0.0, -1.0, t_min, t_max));
+        hit = closest(hit, self.hit_hemisphere(o, d, height, 1.0, t_min, t_max));
+        Some(self.frame.record(r, hit?, &self.mat_ptr))
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        let top = self.frame.base + self.frame.height * self.frame.axis;
+        Some(surrounding_box(&Aabb::new(self.frame.base - radius, self.frame.base + radius),
+                             &Aabb::new(top - radius, top + radius)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::cylinder::{Capsule, Cone, Cylinder};
+    use crate::material::{Lambertian, Scatter};
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    fn material() -> Rc<dyn Scatter> {
+        Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+    }
+
+    fn upright(capped: bool) -> Cylinder {
+        Cylinder::new(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 2.0, 0.0), 1.0, capped, material())
+    }
+
+    #[test]
+    fn check_cylinder_side_and_caps() {
+        let side = Ray::new(Point3::new(-5.0, 1.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = upright(true).hit(&side, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.0, rec.t, 1e-9);
+        assert_approx_eq!(-1.0, rec.normal.x, 1e-9);
+        assert_approx_eq!(0.75, rec.v, 1e-9);
+        assert!(rec.front_face);
+
+        let top = Ray::new(Point3::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = upright(true).hit(&top, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(3.0, rec.t, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-9);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn check_uncapped_cylinder_shows_inside() {
+        let top = Ray::new(Point3::new(0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(upright(false).hit(&top, 0.0, f64::INFINITY).is_none());
+
+        let through = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.25, -1.0, 0.0));
+        let rec = upright(false).hit(&through, 0.0, f64::INFINITY).unwrap();
+        assert!(!rec.front_face);
+        assert!(rec.normal.x < 0.0);
+    }
+
+    #[test]
+    fn check_cone_normal() {
+        let cone = Cone::new(Point3::new(0.0, 0.0, 0.0), 1.0, Point3::new(0.0, 1.0, 0.0), 0.0, true, material());
+        let r = Ray::new(Point3::new(-5.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = cone.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.5, rec.t, 1e-9);
+        assert_approx_eq!(-(0.5_f64).sqrt(), rec.normal.x, 1e-9);
+        assert_approx_eq!((0.5_f64).sqrt(), rec.normal.y, 1e-9);
+
+        let bottom = Ray::new(Point3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let rec = cone.hit(&bottom, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(1.0, rec.t, 1e-9);
+        assert_approx_eq!(-1.0, rec.normal.y, 1e-9);
+    }
+
+    #[test]
+    fn check_cone_apex_normal() {
+        let cone = Cone::new(Point3::new(0.0, 0.0, 0.0), 1.0, Point3::new(0.0, 1.0, 0.0), 0.0, false, material());
+        let r = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = cone.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.0, rec.t, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cone needs non-negative radii, not both zero.")]
+    fn check_cone_needs_a_radius() {
+        Cone::new(Point3::new(0.0, 0.0, 0.0), 0.0, Point3::new(0.0, 1.0, 0.0), 0.0, true, material());
+    }
+
+    #[test]
+    fn check_capsule_ends() {
+        let capsule = Capsule::new(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 2.0, 0.0), 0.5, material());
+        let top = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = capsule.hit(&top, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(2.5, rec.t, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-9);
+        assert_approx_eq!(1.0, rec.v, 1e-9);
+
+        let side = Ray::new(Point3::new(-5.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = capsule.hit(&side, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.5, rec.t, 1e-9);
+
+        let bbox = capsule.bounding_box(0.0, 1.0).unwrap();
+        assert_approx_eq!(-0.5, bbox.min().y, 1e-9);
+        assert_approx_eq!(2.5, bbox.max().y, 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "Axis needs two distinct end points.")]
+    fn check_zero_length_axis_is_rejected() {
+        let p = Point3::new(1.0, 2.0, 3.0);
+        Cylinder::new(p, p, 1.0, true, material());
+    }
+}
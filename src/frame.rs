@@ -0,0 +1,89 @@
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Ray};
+use crate::roots::solve_quadratic;
+use crate::vec::Point3;
+
+// Orthonormal frame whose z axis runs along a shape's axis, with the origin at
+// its base. Rays are intersected in this frame so cylinders, cones, tori and
+// curve segments can be solved as if they stood on the xy plane.
+pub(crate) struct AxisFrame {
+    pub(crate) base: Point3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    pub(crate) axis: Vec3,
+    pub(crate) height: f64,
+}
+
+impl AxisFrame {
+    pub(crate) fn new(p0: Point3, p1: Point3) -> AxisFrame {
+        let height = (p1 - p0).length();
+        assert!(height > 0.0, "Axis needs two distinct end points.");
+        let axis = vec::unit_vector(p1 - p0);
+        let (tangent, bitangent) = vec::tangent_frame(axis);
+        AxisFrame { base: p0, tangent, bitangent, axis, height }
+    }
+
+    pub(crate) fn to_local(&self, v: Vec3) -> Vec3 {
+        Vec3::new(vec::dot(&v, &self.tangent), vec::dot(&v, &self.bitangent), vec::dot(&v, &self.axis))
+    }
+
+    pub(crate) fn to_world(&self, v: Vec3) -> Vec3 {
+        v.x * self.tangent + v.y * self.bitangent + v.z * self.axis
+    }
+
+    pub(crate) fn local_ray(&self, r: &Ray) -> (Point3, Vec3) {
+        (self.to_local(r.origin() - self.base), self.to_local(r.direction()))
+    }
+
+    pub(crate) fn record(&self, r: &Ray, hit: SurfaceHit, mat_ptr: &Rc<dyn Scatter>) -> HitRecord {
+        let outward_normal = vec::unit_vector(self.to_world(hit.normal));
+        let mut rec = HitRecord::new(r.at(hit.t), outward_normal, Rc::clone(mat_ptr), hit.t);
+        rec.u = hit.u;
+        rec.v = hit.v;
+        rec.set_face_normal(r);
+        rec
+    }
+}
+
+// Intersection in the local frame of an AxisFrame.
+pub(crate) struct SurfaceHit {
+    pub(crate) t: f64,
+    pub(crate) normal: Vec3,
+    pub(crate) u: f64,
+    pub(crate) v: f64,
+}
+
+pub(crate) fn azimuth(p: Point3) -> f64 {
+    (f64::atan2(p.y, p.x) + PI) / (2.0 * PI)
+}
+
+// Lateral surface of a truncated cone between z = 0 (radius r0) and z = height
+// (radius r1); a cylinder when the radii match.
+pub(crate) fn hit_frustum_side(o: Point3, d: Vec3, r0: f64, r1: f64, height: f64, t_min: f64, t_max: f64) -> Option<SurfaceHit> {
+    let k = (r1 - r0) / height;
+    let radius_at_origin = r0 + k * o.z;
+    let a = d.x * d.x + d.y * d.y - k * k * d.z * d.z;
+    let b = 2.0 * (o.x * d.x + o.y * d.y - k * d.z * radius_at_origin);
+    let c = o.x * o.x + o.y * o.y - radius_at_origin * radius_at_origin;
+    let (t0, t1) = solve_quadratic(a, b, c)?;
+    for t in [t0, t1] {
+        if t < t_min || t > t_max {
+            continue;
+        }
+        let p = o + t * d;
+        if p.z < 0.0 || p.z > height {
+            continue;
+        }
+        let mut normal = Vec3::new(p.x, p.y, -k * (r0 + k * p.z));
+        if normal.near_zero() {
+            // The apex of a pointed cone: face out along the axis.
+            normal = Vec3::new(0.0, 0.0, -k.signum());
+        }
+        return Some(SurfaceHit { t, normal, u: azimuth(p), v: p.z / height });
+    }
+    None
+}
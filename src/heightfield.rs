@@ -0,0 +1,220 @@
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::Aabb;
+use crate::image::{Image, ImageError, load_image};
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::triangle::intersect;
+use crate::vec::Point3;
+
+// Terrain from a regular grid of heights. Samples are spread over `size.x` by
+// `size.z` starting at `corner`, and a height of 1 rises `size.y` above it. Each
+// grid cell is split into two triangles; rays walk the cells they cross in order,
+// so only a thin line of cells is ever tested.
+pub struct Heightfield {
+    columns: usize,
+    rows: usize,
+    corner: Point3,
+    size: Vec3,
+    // World-space heights, row by row along z.
+    heights: Vec<f64>,
+    normals: Vec<Vec3>,
+    bbox: Aabb,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+// Loads a PNG, PGM or PPM image as a heightfield: brighter pixels are higher, and the
+// top row of the image lies along the far (maximum z) edge.
+pub fn load_heightfield(path: &str, corner: Point3, size: Vec3, mat_ptr: Rc<dyn Scatter>) -> Result<Heightfield, ImageError> {
+    let image = load_image(path)?;
+    if image.width() < 2 || image.height() < 2 {
+        return Err(ImageError::Format { file: path.to_string(), message: "heightfield needs at least 2x2 pixels".to_string() });
+    }
+    Ok(Heightfield::from_image(&image, corner, size, mat_ptr))
+}
+
+impl Heightfield {
+    // `heights` holds `columns` samples along x for each of `rows` along z, in [0, 1].
+    pub fn new(heights: Vec<f64>, columns: usize, rows: usize, corner: Point3, size: Vec3, mat_ptr: Rc<dyn Scatter>) -> Heightfield {
+        assert!(columns >= 2 && rows >= 2, "Heightfield needs at least 2x2 samples.");
+        assert_eq!(columns * rows, heights.len(), "Heightfield needs columns * rows samples.");
+        let heights: Vec<f64> = heights.iter().map(|h| corner.y + h * size.y).collect();
+        let low = heights.iter().copied().fold(f64::INFINITY, f64::min);
+        let high = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let bbox = Aabb::new(Point3::new(corner.x, low, corner.z), Point3::new(corner.x + size.x, high, corner.z + size.z)).pad(0.0001);
+
+        let mut field = Heightfield { columns, rows, corner, size, heights, normals: Vec::new(), bbox, mat_ptr };
+        field.normals = (0..rows).flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| field.vertex_normal(i, j))
+            .collect();
+        field
+    }
+
+    pub fn from_image(image: &Image, corner: Point3, size: Vec3, mat_ptr: Rc<dyn Scatter>) -> Heightfield {
+        let (columns, rows) = (image.width(), image.height());
+        let heights = (0..rows).flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| image.luminance(i, rows - 1 - j))
+            .collect();
+        Heightfield::new(heights, columns, rows, corner, size, mat_ptr)
+    }
+
+    fn spacing(&self) -> (f64, f64) {
+        (self.size.x / (self.columns - 1) as f64, self.size.z / (self.rows - 1) as f64)
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        let (dx, dz) = self.spacing();
+        Point3::new(self.corner.x + i as f64 * dx, self.heights[j * self.columns + i], self.corner.z + j as f64 * dz)
+    }
+
+    // Slope from central differences, one-sided at the borders.
+    fn vertex_normal(&self, i: usize, j: usize) -> Vec3 {
+        let (dx, dz) = self.spacing();
+        let (i0, i1) = (i.saturating_sub(1), (i + 1).min(self.columns - 1));
+        let (j0, j1) = (j.saturating_sub(1), (j + 1).min(self.rows - 1));
+        let height = |i: usize, j: usize| self.heights[j * self.columns + i];
+        let slope_x = (height(i1, j) - height(i0, j)) / ((i1 - i0) as f64 * dx);
+        let slope_z = (height(i, j1) - height(i, j0)) / ((j1 - j0) as f64 * dz);
+        vec::unit_vector(Vec3::new(-slope_x, 1.0, -slope_z))
+    }
+
+    // Closest hit with the two triangles of cell (i, j).
+    fn hit_cell(&self, r: &Ray, i: usize, j: usize, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j + 1), (i + 1, j), (i, j + 1)];
+        let mut closest: Option<HitRecord> = None;
+        for [a, b, c] in [[corners[0], corners[2], corners[1]], [corners[0], corners[1], corners[3]]] {
+            let t_max = closest.as_ref().map_or(t_max, |rec| rec.t);
+            let Some((t, b1, b2)) = intersect(r, self.vertex(a.0, a.1), self.vertex(b.0, b.1), self.vertex(c.0, c.1), t_min, t_max) else {
+                continue;
+            };
+            let b0 = 1.0 - b1 - b2;
+            let normal = |(i, j): (usize, usize)| self.normals[j * self.columns + i];
+            let shading_normal = vec::unit_vector(b0 * normal(a) + b1 * normal(b) + b2 * normal(c));
+            let mut rec = HitRecord::new(r.at(t), shading_normal, Rc::clone(&self.mat_ptr), t);
+            rec.set_face_normal(r);
+            let grid = |i: usize, j: usize| (i as f64 / (self.columns - 1) as f64, j as f64 / (self.rows - 1) as f64);
+            let (ua, va) = grid(a.0, a.1);
+            let (ub, vb) = grid(b.0, b.1);
+            let (uc, vc) = grid(c.0, c.1);
+            rec.u = b0 * ua + b1 * ub + b2 * uc;
+            rec.v = b0 * va + b1 * vb + b2 * vc;
+            closest = Some(rec);
+        }
+        closest
+    }
+}
+
+impl Hittable for Heightfield {
+    // 2D DDA over the cells under the ray (Amanatides & Woo). A hit inside a cell
+    // is closer than anything in the cells that follow, so the walk stops there.
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bbox.clip(r, t_min, t_max)?;
+        let (dx, dz) = self.spacing();
+        let (origin, direction) = (r.origin(), r.direction());
+        let start = r.at(t_enter);
+        let cell = |offset: f64, spacing: f64, cells: usize| ((offset / spacing).floor().max(0.0) as usize).min(cells - 1);
+        let mut i = cell(start.x - self.corner.x, dx, self.columns - 1);
+        let mut j = cell(start.z - self.corner.z, dz, self.rows - 1);
+
+        // Ray parameter at the next cell boundary along an axis, and between boundaries.
+        let setup = |index: usize, spacing: f64, corner: f64, o: f64, d: f64| {
+            if d > 0.0 {
+                ((corner + (index + 1) as f64 * spacing - o) / d, spacing / d)
+            } else if d < 0.0 {
+                ((corner + index as f64 * spacing - o) / d, -spacing / d)
+            } else {
+                (f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (mut next_x, delta_x) = setup(i, dx, self.corner.x, origin.x, direction.x);
+        let (mut next_z, delta_z) = setup(j, dz, self.corner.z, origin.z, direction.z);
+
+        loop {
+            let cell_exit = next_x.min(next_z).min(t_exit);
+            if let Some(rec) = self.hit_cell(r, i, j, t_min, t_max) {
+                return Some(rec);
+            }
+            if cell_exit >= t_exit {
+                return None;
+            }
+            if next_x < next_z {
+                if direction.x > 0.0 { i += 1 } else { i = i.checked_sub(1)? }
+                next_x += delta_x;
+            } else {
+                if direction.z > 0.0 { j += 1 } else { j = j.checked_sub(1)? }
+                next_z += delta_z;
+            }
+            if i >= self.columns - 1 || j >= self.rows - 1 {
+                return None;
+            }
+        }
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::heightfield::Heightfield;
+    use crate::image::parse_pnm;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    fn field(heights: Vec<f64>, columns: usize, rows: usize) -> Heightfield {
+        Heightfield::new(heights, columns, rows, Point3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 2.0, 4.0),
+                         Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_flat_field() {
+        let flat = field(vec![0.5; 25], 5, 5);
+        let r = Ray::new(Point3::new(1.3, 5.0, 2.9), Vec3::new(0.0, -1.0, 0.0));
+        let rec = flat.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.0, rec.t, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-9);
+        assert_approx_eq!(1.3 / 4.0, rec.u, 1e-9);
+        assert_approx_eq!(2.9 / 4.0, rec.v, 1e-9);
+    }
+
+    #[test]
+    fn check_walks_cells_to_the_hill() {
+        // A single peak in the middle of a 5x5 grid.
+        let mut heights = vec![0.0; 25];
+        heights[12] = 1.0;
+        let hill = field(heights, 5, 5);
+        let r = Ray::new(Point3::new(-1.0, 1.0, 1.75), Vec3::new(1.0, 0.0, 0.0));
+        let rec = hill.hit(&r, 0.0, f64::INFINITY).unwrap();
+        // The slope rises from x = 1 to the peak height of 2 at x = 2, reaching y = 1 half way.
+        assert_approx_eq!(2.5, rec.t, 1e-9);
+        assert!(rec.front_face);
+        assert!(rec.normal.x < 0.0 && rec.normal.y > 0.0);
+
+        // From the other side the cell diagonals make the face steeper.
+        let back = Ray::new(Point3::new(5.0, 1.0, 1.75), Vec3::new(-1.0, 0.0, 0.0));
+        assert_approx_eq!(2.75, hill.hit(&back, 0.0, f64::INFINITY).unwrap().t, 1e-9);
+
+        let over = Ray::new(Point3::new(-1.0, 2.5, 1.75), Vec3::new(1.0, 0.0, 0.0));
+        assert!(hill.hit(&over, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_from_image() {
+        // Top image row becomes the far edge, so the bright row ends up at z = 4.
+        let image = parse_pnm(b"P2 2 2 255\n255 255\n0 0\n", "ramp.pgm").unwrap();
+        let ramp = Heightfield::from_image(&image, Point3::new(0.0, 0.0, 0.0), Vec3::new(4.0, 2.0, 4.0),
+                                           Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let r = Ray::new(Point3::new(2.0, 5.0, 3.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = ramp.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(3.5, rec.t, 1e-9);
+        assert_approx_eq!(-1.0 / 5.0_f64.sqrt(), rec.normal.z, 1e-9);
+    }
+}
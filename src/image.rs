@@ -0,0 +1,258 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+
+use png::{ColorType, Transformations};
+
+use crate::color::Color;
+
+// Decoded raster with channels scaled to [0, 1]. Row 0 is the top of the image.
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Image {
+        assert_eq!(width * height, pixels.len(), "Image needs width * height pixels.");
+        Image { width, height, pixels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    // Rec. 709 luma, so colour images can be read as height or mask maps.
+    pub fn luminance(&self, x: usize, y: usize) -> f64 {
+        let c = self.pixel(x, y);
+        0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+    }
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io { file: String, source: io::Error },
+    Format { file: String, message: String },
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io { file, source } => write!(f, "{}: {}", file, source),
+            ImageError::Format { file, message } => write!(f, "{}: {}", file, message),
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// Loads a PGM or PPM image, in plain (P2, P3) or raw (P5, P6) form.
+pub fn load_pnm(path: &str) -> Result<Image, ImageError> {
+    let bytes = fs::read(path).map_err(|source| ImageError::Io { file: path.to_string(), source })?;
+    parse_pnm(&bytes, path)
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// Loads a PNG, PGM or PPM image, telling them apart by their first bytes.
+pub fn load_image(path: &str) -> Result<Image, ImageError> {
+    let bytes = fs::read(path).map_err(|source| ImageError::Io { file: path.to_string(), source })?;
+    if bytes.starts_with(PNG_SIGNATURE) {
+        parse_png(&bytes, path)
+    } else {
+        parse_pnm(&bytes, path)
+    }
+}
+
+// Decodes a PNG of any bit depth or color type; alpha is dropped.
+pub fn parse_png(bytes: &[u8], file: &str) -> Result<Image, ImageError> {
+    let format_error = |e: png::DecodingError| ImageError::Format { file: file.to_string(), message: e.to_string() };
+    let mut decoder = png::Decoder::new(bytes);
+    // Palettes and low bit depths are expanded and 16-bit samples cut to 8 bits.
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(format_error)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(format_error)?;
+
+    let channels = match info.color_type {
+        ColorType::Grayscale => 1,
+        ColorType::GrayscaleAlpha => 2,
+        ColorType::Rgb => 3,
+        ColorType::Rgba => 4,
+        ColorType::Indexed => unreachable!("palettes are expanded by the decoder"),
+    };
+    let (width, height) = (info.width as usize, info.height as usize);
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in buffer.chunks_exact(info.line_size).take(height) {
+        for s in row.chunks_exact(channels).take(width) {
+            let (r, g, b) = if channels < 3 { (s[0], s[0], s[0]) } else { (s[0], s[1], s[2]) };
+            pixels.push(Color::new(r as f64, g as f64, b as f64) / 255.0);
+        }
+    }
+    Ok(Image::new(width, height, pixels))
+}
+
+// Reads whitespace-separated header and plain-format tokens, skipping `#` comments.
+struct PnmReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl PnmReader<'_> {
+    fn token(&mut self) -> Option<&str> {
+        loop {
+            match self.bytes.get(self.pos)? {
+                b'#' => {
+                    while self.bytes.get(self.pos).is_some_and(|&b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos]).ok()
+    }
+
+    fn number(&mut self, what: &str, file: &str) -> Result<usize, ImageError> {
+        let token = self.token().unwrap_or("");
+        token.parse().map_err(|_| ImageError::Format { file: file.to_string(), message: format!("invalid {} '{}'", what, token) })
+    }
+}
+
+pub fn parse_pnm(bytes: &[u8], file: &str) -> Result<Image, ImageError> {
+    let format_error = |message: String| ImageError::Format { file: file.to_string(), message };
+    let mut reader = PnmReader { bytes, pos: 0 };
+    let magic = reader.token().unwrap_or("").to_string();
+    let (channels, binary) = match magic.as_str() {
+        "P2" => (1, false),
+        "P3" => (3, false),
+        "P5" => (1, true),
+        "P6" => (3, true),
+        _ => return Err(format_error(format!("unsupported image type '{}'", magic))),
+    };
+    let width = reader.number("width", file)?;
+    let height = reader.number("height", file)?;
+    let max_value = reader.number("maximum value", file)?;
+    if max_value == 0 || max_value > 65535 {
+        return Err(format_error(format!("invalid maximum value {}", max_value)));
+    }
+
+    // The header is untrusted, so the sample count is checked against the data
+    // before anything is allocated for it.
+    let count = width.checked_mul(height).and_then(|n| n.checked_mul(channels))
+        .ok_or_else(|| format_error(format!("image size {}x{} is too large", width, height)))?;
+    let mut samples;
+    if binary {
+        // A single whitespace byte separates the header from the raster.
+        let raster = &bytes[(reader.pos + 1).min(bytes.len())..];
+        let sample_size = if max_value < 256 { 1 } else { 2 };
+        let expected = count.checked_mul(sample_size).filter(|&n| n <= raster.len())
+            .ok_or_else(|| format_error(format!("expected {} samples of pixel data, found {} bytes", count, raster.len())))?;
+        samples = Vec::with_capacity(count);
+        for sample in raster[..expected].chunks_exact(sample_size) {
+            samples.push(sample.iter().fold(0, |acc, &b| acc << 8 | b as usize));
+        }
+    } else {
+        // Every plain sample takes at least one byte.
+        let remaining = bytes.len() - reader.pos;
+        if count > remaining {
+            return Err(format_error(format!("expected {} samples of pixel data, found {} bytes", count, remaining)));
+        }
+        samples = Vec::with_capacity(count);
+        for _ in 0..count {
+            samples.push(reader.number("sample", file)?);
+        }
+    }
+    if let Some(sample) = samples.iter().find(|&&sample| sample > max_value) {
+        return Err(format_error(format!("sample {} exceeds maximum value {}", sample, max_value)));
+    }
+
+    let scale = 1.0 / max_value as f64;
+    let pixels = samples.chunks_exact(channels)
+        .map(|s| match s {
+            [gray] => Color::new(*gray as f64, *gray as f64, *gray as f64) * scale,
+            _ => Color::new(s[0] as f64, s[1] as f64, s[2] as f64) * scale,
+        })
+        .collect();
+    Ok(Image::new(width, height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::image::{parse_png, parse_pnm};
+
+    #[test]
+    fn check_plain_ppm() {
+        let text = b"P3\n# two pixels\n2 1\n255\n255 0 0  0 0 255\n";
+        let image = parse_pnm(text, "test.ppm").unwrap();
+        assert_eq!((2, 1), (image.width(), image.height()));
+        assert_eq!(format!("{}", image.pixel(1, 0)), "0 0 1");
+        assert_approx_eq!(0.2126, image.luminance(0, 0), 1e-12);
+    }
+
+    #[test]
+    fn check_raw_pgm() {
+        let mut bytes = b"P5 2 2 65535\n".to_vec();
+        bytes.extend_from_slice(&[0, 0, 0xff, 0xff, 0x80, 0x00, 0, 0]);
+        let image = parse_pnm(&bytes, "test.pgm").unwrap();
+        assert_approx_eq!(1.0, image.luminance(1, 0), 1e-12);
+        assert_approx_eq!(32768.0 / 65535.0, image.pixel(0, 1).y, 1e-12);
+    }
+
+    #[test]
+    fn check_errors() {
+        let err = parse_pnm(b"P6 2 2 255\n\x01\x02", "short.ppm").err().unwrap();
+        assert_eq!(err.to_string(), "short.ppm: expected 12 samples of pixel data, found 2 bytes");
+        let err = parse_pnm(b"P3 2 x 255", "bad.ppm").err().unwrap();
+        assert_eq!(err.to_string(), "bad.ppm: invalid height 'x'");
+        let err = parse_pnm(b"P2 2 1 255\n12 999\n", "bright.pgm").err().unwrap();
+        assert_eq!(err.to_string(), "bright.pgm: sample 999 exceeds maximum value 255");
+        let err = parse_pnm(b"P2 100000 100000 255\n0 0 0", "huge.pgm").err().unwrap();
+        assert_eq!(err.to_string(), "huge.pgm: expected 10000000000 samples of pixel data, found 6 bytes");
+        let err = parse_pnm(format!("P6 {} {} 255\n", usize::MAX, 2).as_bytes(), "overflow.ppm").err().unwrap();
+        assert!(err.to_string().ends_with("is too large"));
+    }
+
+    #[test]
+    fn check_png() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[255, 0, 0, 255, 0, 51, 255, 0]).unwrap();
+        }
+        let image = parse_png(&bytes, "test.png").unwrap();
+        assert_eq!((2, 1), (image.width(), image.height()));
+        assert_eq!(format!("{}", image.pixel(0, 0)), "1 0 0");
+        assert_eq!(format!("{}", image.pixel(1, 0)), "0 0.2 1");
+
+        let err = parse_png(&bytes[..20], "cut.png").err().unwrap();
+        assert!(err.to_string().starts_with("cut.png: "));
+    }
+}
@@ -0,0 +1,102 @@
+use std::rc::Rc;
+
+use crate::aabb::Aabb;
+use crate::material::Scatter;
+use crate::matrix::Matrix4;
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::transform::Transform;
+
+// One placement of shared geometry. Instances hold only a reference to the
+// geometry plus their own matrices, so a mesh and its BVH are stored once however
+// many copies are in the scene. Put the instances themselves in a `BvhNode` to
+// keep large sets fast.
+pub struct Instance {
+    transform: Transform,
+    mat_override: Option<Rc<dyn Scatter>>,
+}
+
+impl Instance {
+    pub fn new(geometry: Rc<dyn Hittable>, object_to_world: Matrix4) -> Instance {
+        Instance { transform: Transform::new(geometry, object_to_world), mat_override: None }
+    }
+
+    // Renders this instance with `mat_ptr` instead of the geometry's own materials.
+    pub fn with_material(mut self, mat_ptr: Rc<dyn Scatter>) -> Instance {
+        self.mat_override = Some(mat_ptr);
+        self
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut rec = self.transform.hit(r, t_min, t_max)?;
+        if let Some(mat_ptr) = &self.mat_override {
+            rec.mat_ptr = Rc::clone(mat_ptr);
+        }
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.transform.bounding_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::bvh::BvhNode;
+    use crate::color::Color;
+    use crate::instance::Instance;
+    use crate::material::{Lambertian, Metal, Scatter};
+    use crate::matrix::Matrix4;
+    use crate::mesh::{MeshData, MeshFace, TriangleMesh};
+    use crate::raytracing::{Hittable, HittableList, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    // Unit square in the xy plane.
+    fn tile() -> Rc<dyn Hittable> {
+        let mut data = MeshData::new();
+        data.positions = vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0),
+                              Point3::new(1.0, 1.0, 0.0), Point3::new(0.0, 1.0, 0.0)];
+        data.faces = vec![MeshFace { positions: [0, 1, 2], normals: None, uvs: None },
+                          MeshFace { positions: [0, 2, 3], normals: None, uvs: None }];
+        Rc::new(TriangleMesh::new(data, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))))
+    }
+
+    #[test]
+    fn check_instances_share_geometry() {
+        let geometry = tile();
+        let mut instances = HittableList::new();
+        for i in 0..100 {
+            let placement = Matrix4::translation(Vec3::new(2.0 * i as f64, 0.0, -(i as f64)));
+            instances.add(Rc::new(Instance::new(Rc::clone(&geometry), placement)));
+        }
+        assert_eq!(101, Rc::strong_count(&geometry));
+
+        let forest = BvhNode::new(&instances, 0.0, 1.0);
+        let r = Ray::new(Point3::new(84.5, 0.5, 10.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = forest.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(52.0, rec.t, 1e-9);
+        assert_approx_eq!(-42.0, rec.p.z, 1e-9);
+        assert!(forest.hit(&Ray::new(Point3::new(85.5, 0.5, 10.0), Vec3::new(0.0, 0.0, -1.0)), 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_material_override() {
+        let geometry = tile();
+        let chrome: Rc<dyn Scatter> = Rc::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.0));
+        let plain = Instance::new(Rc::clone(&geometry), Matrix4::identity());
+        let shiny = Instance::new(geometry, Matrix4::rotation_y(180.0)).with_material(Rc::clone(&chrome));
+
+        let r = Ray::new(Point3::new(0.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(!Rc::ptr_eq(&plain.hit(&r, 0.0, f64::INFINITY).unwrap().mat_ptr, &chrome));
+        let r = Ray::new(Point3::new(-0.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = shiny.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert!(Rc::ptr_eq(&rec.mat_ptr, &chrome));
+        // The tile is seen from behind after the half turn.
+        assert!(!rec.front_face);
+    }
+}
@@ -3,9 +3,38 @@ extern crate core;
 use crate::vec::Vec3;
 
 pub mod vec;
+pub mod frame;
 pub mod color;
 pub mod raytracing;
 pub mod camera;
 pub mod material;
+pub mod aabb;
+pub mod bvh;
+pub mod triangle;
+pub mod mesh;
+pub mod obj;
+pub mod ply;
+pub mod stl;
+pub mod aarect;
+pub mod cuboid;
+pub mod quad;
+pub mod plane;
+pub mod cylinder;
+pub mod roots;
+pub mod torus;
+pub mod matrix;
+pub mod transform;
+pub mod moving_sphere;
+pub mod constant_medium;
+pub mod csg;
+pub mod sdf;
+pub mod image;
+pub mod heightfield;
+pub mod instance;
+pub mod metaballs;
+pub mod curve;
+pub mod texture;
+pub mod perlin;
+pub mod procedural;
 
 
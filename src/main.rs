@@ -2,35 +2,45 @@ use std::fs::File;
 use std::io::Write;
 use std::rc::Rc;
 
+use ray_tracing_in_one_weekend::aarect::{XYRect, XZRect, YZRect};
+use ray_tracing_in_one_weekend::bvh::BvhNode;
 use ray_tracing_in_one_weekend::camera::Camera;
 use ray_tracing_in_one_weekend::color;
 use ray_tracing_in_one_weekend::color::Color;
-use ray_tracing_in_one_weekend::material::{Dielectric, Lambertian, Metal, Scatter};
-use ray_tracing_in_one_weekend::raytracing::{HittableList, random_double, random_double_range, ray_color, Sphere};
+use ray_tracing_in_one_weekend::cuboid::Cuboid;
+use ray_tracing_in_one_weekend::material::{Dielectric, DiffuseLight, Lambertian, Metal, Scatter};
+use ray_tracing_in_one_weekend::matrix::Matrix4;
+use ray_tracing_in_one_weekend::moving_sphere::MovingSphere;
+use ray_tracing_in_one_weekend::plane::Plane;
+use ray_tracing_in_one_weekend::raytracing::{Background, HittableList, random_double, random_double_range, ray_color, Sphere};
+use ray_tracing_in_one_weekend::texture::UvChecker;
+use ray_tracing_in_one_weekend::transform::Transform;
 use ray_tracing_in_one_weekend::vec::{Point3, Vec3};
 
+struct Scene {
+    path: &'static str,
+    world: HittableList,
+    camera: Camera,
+    background: Background,
+    aspect_ratio: f64,
+}
+
+// Renders the scene named by the first argument: `random` (default), `bouncing`
+// or `cornell`.
 fn main() {
-    let path = "images/image_19.ppm";
-    let mut f = File::create(path).unwrap();
+    let scene = match std::env::args().nth(1).as_deref() {
+        Some("bouncing") => bouncing_scene(),
+        Some("cornell") => cornell_scene(),
+        _ => random_scene_setup(),
+    };
+    let mut f = File::create(scene.path).unwrap();
 
     //image
     let image_width = 1200;
-    let aspect_ratio = 3.0 / 2.0;
-    let image_height = (image_width as f64 / aspect_ratio) as i32;
+    let image_height = (image_width as f64 / scene.aspect_ratio) as i32;
     let samples_per_pixel = 500;
     let max_depth = 50;
 
-    let world = random_scene();
-
-    //camera
-    let look_from = Point3::new(13.0, 2.0, 3.0);
-    let look_at = Point3::new(0.0, 0.0, 0.0);
-    let vup = Vec3::new(0.0, 1.0, 0.0);
-    let dist_to_focus = 10.0;
-    let aperture = 0.1;
-    let camera = Camera::new(look_from, look_at, vup, 20.0, aspect_ratio, aperture,
-                             dist_to_focus);
-
     //render
     writeln!(f, "P3\n{} {}\n255", image_width, image_height).unwrap();
 
@@ -40,28 +50,99 @@ fn main() {
             for _ in 0..samples_per_pixel {
                 let u = (i as f64 + random_double()) / (image_width - 1) as f64;
                 let v = (j as f64 + random_double()) / (image_height - 1) as f64;
-                let r = camera.get_ray(u, v);
-                pixel_color = pixel_color + ray_color(&r, &world, max_depth);
+                let r = scene.camera.get_ray(u, v);
+                pixel_color = pixel_color + ray_color(&r, &scene.background, &scene.world, max_depth);
             }
             color::write_color(&mut f, &pixel_color, samples_per_pixel);
         }
     }
 }
 
+fn random_scene_setup() -> Scene {
+    let aspect_ratio = 3.0 / 2.0;
+    let look_from = Point3::new(13.0, 2.0, 3.0);
+    let look_at = Point3::new(0.0, 0.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 10.0;
+    let aperture = 0.1;
+    let camera = Camera::new(look_from, look_at, vup, 20.0, aspect_ratio, aperture,
+                             dist_to_focus);
+    Scene { path: "images/image_19.ppm", world: random_scene(), camera, background: Background::Sky, aspect_ratio }
+}
+
+// The random scene with its diffuse spheres bouncing up while the shutter is
+// open, rendered with motion blur.
+fn bouncing_scene() -> Scene {
+    let aspect_ratio = 3.0 / 2.0;
+    let look_from = Point3::new(13.0, 2.0, 3.0);
+    let look_at = Point3::new(0.0, 0.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+    let camera = Camera::new(look_from, look_at, vup, 20.0, aspect_ratio, 0.1, 10.0).with_shutter(0.0, 1.0);
+    Scene { path: "images/image_21.ppm", world: random_spheres(true), camera, background: Background::Sky, aspect_ratio }
+}
+
+fn cornell_scene() -> Scene {
+    let aspect_ratio = 1.0;
+    let look_from = Point3::new(278.0, 278.0, -800.0);
+    let look_at = Point3::new(278.0, 278.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+    let camera = Camera::new(look_from, look_at, vup, 40.0, aspect_ratio, 0.0, 10.0);
+    Scene {
+        path: "images/image_20.ppm",
+        world: cornell_box(),
+        camera,
+        background: Background::Solid(Color::new(0.0, 0.0, 0.0)),
+        aspect_ratio,
+    }
+}
+
+// Closed room lit only by a lamp in the ceiling.
+pub fn cornell_box() -> HittableList {
+    let mut world = HittableList::new();
+    let red = Rc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Rc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Rc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Rc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
+
+    world.add(Rc::new(YZRect::new(0.0, 555.0, 0.0, 555.0, 555.0, green)));
+    world.add(Rc::new(YZRect::new(0.0, 555.0, 0.0, 555.0, 0.0, red)));
+    world.add(Rc::new(XZRect::new(213.0, 343.0, 227.0, 332.0, 554.0, light)));
+    world.add(Rc::new(XZRect::new(0.0, 555.0, 0.0, 555.0, 0.0, white.clone())));
+    world.add(Rc::new(XZRect::new(0.0, 555.0, 0.0, 555.0, 555.0, white.clone())));
+    world.add(Rc::new(XYRect::new(0.0, 555.0, 0.0, 555.0, 555.0, white.clone())));
+
+    let tall = Rc::new(Cuboid::new(Point3::new(0.0, 0.0, 0.0), Point3::new(165.0, 330.0, 165.0), white.clone()));
+    world.add(Rc::new(Transform::new(tall, Matrix4::translation(Vec3::new(265.0, 0.0, 295.0)) * Matrix4::rotation_y(15.0))));
+    let short = Rc::new(Cuboid::new(Point3::new(0.0, 0.0, 0.0), Point3::new(165.0, 165.0, 165.0), white));
+    world.add(Rc::new(Transform::new(short, Matrix4::translation(Vec3::new(130.0, 0.0, 65.0)) * Matrix4::rotation_y(-18.0))));
+    world
+}
+
 pub fn random_scene() -> HittableList {
+    random_spheres(false)
+}
+
+// The small diffuse spheres move up by as much as 0.5 between times 0 and 1
+// when `bouncing` is set.
+fn random_spheres(bouncing: bool) -> HittableList {
     // World
     let mut world: HittableList = HittableList::new();
-    let material_ground = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
-    world.add(Rc::new(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, material_ground.clone())));
+    // The plane's UVs are in world units, so this tiles it with unit squares.
+    let checker = Rc::new(UvChecker::from_colors(1.0, 1.0, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    let material_ground = Rc::new(Lambertian::from_texture(checker));
+    world.add(Rc::new(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), material_ground.clone())));
+
+    // The ground plane is unbounded, so only the spheres go into the BVH.
+    let mut objects: HittableList = HittableList::new();
 
     let material1 = Rc::new(Dielectric::new(1.5));
-    world.add(Rc::new(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1.clone())));
+    objects.add(Rc::new(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material1.clone())));
 
     let material2 = Rc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
-    world.add(Rc::new(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material2.clone())));
+    objects.add(Rc::new(Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, material2.clone())));
 
     let material3 = Rc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
-    world.add(Rc::new(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3.clone())));
+    objects.add(Rc::new(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3.clone())));
 
     for a in -11..11 {
         for b in -11..11 {
@@ -75,20 +156,26 @@ pub fn random_scene() -> HittableList {
                     // diffuse
                     let albedo = Color::random() * Color::random();
                     sphere_material = Rc::new(Lambertian::new(albedo));
-                    world.add(Rc::new(Sphere::new(center, 0.2, sphere_material)));
+                    if bouncing {
+                        let center2 = center + Vec3::new(0.0, random_double_range(0.0, 0.5), 0.0);
+                        objects.add(Rc::new(MovingSphere::new(center, center2, 0.0, 1.0, 0.2, sphere_material)));
+                    } else {
+                        objects.add(Rc::new(Sphere::new(center, 0.2, sphere_material)));
+                    }
                 } else if choose_mat < 0.95 {
                     // metal
                     let albedo = Color::random_in_range(0.5, 1.0);
                     let fuzz = random_double_range(0.0, 0.5);
                     sphere_material = Rc::new(Metal::new(albedo, fuzz));
-                    world.add(Rc::new(Sphere::new(center, 0.2, sphere_material)));
+                    objects.add(Rc::new(Sphere::new(center, 0.2, sphere_material)));
                 } else {
                     // glass
                     sphere_material = Rc::new(Dielectric::new(1.5));
-                    world.add(Rc::new(Sphere::new(center, 0.2, sphere_material)));
+                    objects.add(Rc::new(Sphere::new(center, 0.2, sphere_material)));
                 }
             }
         }
     }
+    world.add(Rc::new(BvhNode::new(&objects, 0.0, 1.0)));
     world
 }
\ No newline at end of file
@@ -1,37 +1,49 @@
 use core::f64;
+use std::rc::Rc;
 
 use crate::{raytracing, vec, Vec3};
 use crate::color::Color;
 use crate::raytracing::{HitRecord, Ray};
+use crate::texture::{SolidColor, Texture};
+use crate::vec::Point3;
 
 pub trait Scatter {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)>;
+
+    // Light given off at the hit point; only light sources emit anything.
+    fn emitted(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
 }
 
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Rc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Lambertian {
+        Lambertian::from_texture(Rc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(albedo: Rc<dyn Texture>) -> Lambertian {
         Lambertian { albedo }
     }
 }
 
 impl Scatter for Lambertian {
-    fn scatter(&self, _: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
         let mut scatter_direction = rec.normal + Vec3::random_unit_vector();
         if scatter_direction.near_zero() {
             scatter_direction = rec.normal;
         }
-        let scattered = Ray::new(rec.p, scatter_direction);
-        let attenuation = self.albedo;
+        let scattered = Ray::with_time(rec.p, scatter_direction, r_in.time());
+        let attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
         Some((scattered, attenuation))
     }
 }
 
 pub struct Metal {
-    albedo: Color,
+    albedo: Rc<dyn Texture>,
     fuzz: f64,
 }
 
@@ -49,7 +61,7 @@ impl Dielectric {
         // Use Schlick's approximation for reflectance.
         let r0 = (1.0 - refraction_ratio) / (1.0 + refraction_ratio);
         let r0 = r0 * r0;
-        return r0 + (1.0 - r0) * ((1.0 - cos_theta).powi(5));
+        r0 + (1.0 - r0) * ((1.0 - cos_theta).powi(5))
     }
 }
 
@@ -66,14 +78,13 @@ impl Scatter for Dielectric {
         let cos_theta = f64::min(vec::dot(&-unit_direction, &rec.normal), 1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
         let cannot_refract = (refraction_ratio * sin_theta) > 1.0;
-        let direction;
-        if cannot_refract || Dielectric::reflectance(cos_theta, refraction_ratio) > raytracing::random_double() {
-            direction = vec::reflect(unit_direction, rec.normal);
+        let direction = if cannot_refract || Dielectric::reflectance(cos_theta, refraction_ratio) > raytracing::random_double() {
+            vec::reflect(unit_direction, rec.normal)
         } else {
-            direction = vec::refract(unit_direction, rec.normal, refraction_ratio);
-        }
+            vec::refract(unit_direction, rec.normal, refraction_ratio)
+        };
 
-        let scattered = Ray::new(rec.p, direction);
+        let scattered = Ray::with_time(rec.p, direction, r_in.time());
         Some((scattered, attenuation))
     }
 }
@@ -81,6 +92,10 @@ impl Scatter for Dielectric {
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Metal {
+        Metal::from_texture(Rc::new(SolidColor::new(albedo)), fuzz)
+    }
+
+    pub fn from_texture(albedo: Rc<dyn Texture>, fuzz: f64) -> Metal {
         if fuzz < 1.0 {
             Metal { albedo, fuzz }
         } else {
@@ -92,10 +107,57 @@ impl Metal {
 impl Scatter for Metal {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
         let reflected = vec::reflect(vec::unit_vector(r_in.direction()), rec.normal);
-        let scattered = Ray::new(rec.p, reflected + self.fuzz * Vec3::random_in_unit_sphere());
-        let attenuation = self.albedo;
+        let scattered = Ray::with_time(rec.p, reflected + self.fuzz * Vec3::random_in_unit_sphere(), r_in.time());
+        let attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
         if vec::dot(&scattered.direction(), &rec.normal) > 0.0 {
             Some((scattered, attenuation))
         } else { None }
     }
-}
\ No newline at end of file
+}
+
+// Phase function of a participating medium: scatters uniformly in all directions.
+pub struct Isotropic {
+    albedo: Rc<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Isotropic {
+        Isotropic::from_texture(Rc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(albedo: Rc<dyn Texture>) -> Isotropic {
+        Isotropic { albedo }
+    }
+}
+
+impl Scatter for Isotropic {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
+        let scattered = Ray::with_time(rec.p, Vec3::random_unit_vector(), r_in.time());
+        Some((scattered, self.albedo.value(rec.u, rec.v, &rec.p)))
+    }
+}
+
+// Light source: emits `emit` from both sides and reflects nothing.
+pub struct DiffuseLight {
+    emit: Rc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> DiffuseLight {
+        DiffuseLight::from_texture(Rc::new(SolidColor::new(emit)))
+    }
+
+    pub fn from_texture(emit: Rc<dyn Texture>) -> DiffuseLight {
+        DiffuseLight { emit }
+    }
+}
+
+impl Scatter for DiffuseLight {
+    fn scatter(&self, _: &Ray, _: &HitRecord) -> Option<(Ray, Color)> {
+        None
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: &Point3) -> Color {
+        self.emit.value(u, v, p)
+    }
+}
@@ -0,0 +1,178 @@
+use std::ops::Mul;
+
+use crate::Vec3;
+use crate::raytracing::degrees_to_radians;
+use crate::vec::Point3;
+
+// Row-major 4x4 matrix for affine transforms of points, vectors and normals.
+// Matrices compose right to left: `a * b` applies `b` first.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Matrix4 {
+    m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Matrix4 {
+        Matrix4 { m }
+    }
+
+    pub fn identity() -> Matrix4 {
+        Matrix4::new([[1.0, 0.0, 0.0, 0.0],
+                      [0.0, 1.0, 0.0, 0.0],
+                      [0.0, 0.0, 1.0, 0.0],
+                      [0.0, 0.0, 0.0, 1.0]])
+    }
+
+    pub fn translation(offset: Vec3) -> Matrix4 {
+        Matrix4::new([[1.0, 0.0, 0.0, offset.x],
+                      [0.0, 1.0, 0.0, offset.y],
+                      [0.0, 0.0, 1.0, offset.z],
+                      [0.0, 0.0, 0.0, 1.0]])
+    }
+
+    pub fn scaling(factors: Vec3) -> Matrix4 {
+        Matrix4::new([[factors.x, 0.0, 0.0, 0.0],
+                      [0.0, factors.y, 0.0, 0.0],
+                      [0.0, 0.0, factors.z, 0.0],
+                      [0.0, 0.0, 0.0, 1.0]])
+    }
+
+    // Counter-clockwise rotation by `angle` degrees around the unit vector `axis`
+    // (Rodrigues' formula).
+    pub fn rotation(axis: Vec3, angle: f64) -> Matrix4 {
+        let theta = degrees_to_radians(angle);
+        let (sin, cos) = theta.sin_cos();
+        let t = 1.0 - cos;
+        let Vec3 { x, y, z } = crate::vec::unit_vector(axis);
+        Matrix4::new([[t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y, 0.0],
+                      [t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x, 0.0],
+                      [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos, 0.0],
+                      [0.0, 0.0, 0.0, 1.0]])
+    }
+
+    pub fn rotation_x(angle: f64) -> Matrix4 {
+        Matrix4::rotation(Vec3::new(1.0, 0.0, 0.0), angle)
+    }
+
+    pub fn rotation_y(angle: f64) -> Matrix4 {
+        Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), angle)
+    }
+
+    pub fn rotation_z(angle: f64) -> Matrix4 {
+        Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), angle)
+    }
+
+    pub fn transpose(&self) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Matrix4::new(m)
+    }
+
+    // Gauss-Jordan elimination with partial pivoting; None for singular matrices.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let mut a = self.m;
+        let mut inv = Matrix4::identity().m;
+        for col in 0..4 {
+            let pivot = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+            let scale = 1.0 / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+                let factor = a[row][col];
+                for j in 0..4 {
+                    a[row][j] -= factor * a[col][j];
+                    inv[row][j] -= factor * inv[col][j];
+                }
+            }
+        }
+        Some(Matrix4::new(inv))
+    }
+
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        let m = &self.m;
+        let x = m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3];
+        let y = m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3];
+        let z = m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3];
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        if w == 1.0 {
+            Point3::new(x, y, z)
+        } else {
+            Point3::new(x, y, z) / w
+        }
+    }
+
+    // Directions ignore the translation column.
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+                  m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+                  m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z)
+    }
+}
+
+impl Mul<Matrix4> for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Matrix4) -> Self::Output {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        Matrix4::new(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::matrix::Matrix4;
+    use crate::vec::{Point3, Vec3};
+
+    #[test]
+    fn check_rotation() {
+        let p = Matrix4::rotation_y(90.0).transform_point(Point3::new(1.0, 0.0, 0.0));
+        assert_approx_eq!(0.0, p.x, 1e-9);
+        assert_approx_eq!(-1.0, p.z, 1e-9);
+        let p = Matrix4::rotation_z(90.0).transform_point(Point3::new(1.0, 0.0, 0.0));
+        assert_approx_eq!(1.0, p.y, 1e-9);
+    }
+
+    #[test]
+    fn check_composition_order() {
+        let m = Matrix4::translation(Vec3::new(1.0, 0.0, 0.0)) * Matrix4::scaling(Vec3::new(2.0, 2.0, 2.0));
+        let p = m.transform_point(Point3::new(1.0, 1.0, 1.0));
+        assert_eq!(format!("{}", p), "3 2 2");
+        assert_eq!(format!("{}", m.transform_vector(Vec3::new(1.0, 1.0, 1.0))), "2 2 2");
+    }
+
+    #[test]
+    fn check_inverse() {
+        let m = Matrix4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Matrix4::rotation(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            * Matrix4::scaling(Vec3::new(2.0, 0.5, 3.0));
+        let product = m * m.inverse().unwrap();
+        for i in 0..4 {
+            for j in 0..4 {
+                assert_approx_eq!(if i == j { 1.0 } else { 0.0 }, product.m[i][j], 1e-9);
+            }
+        }
+        assert!(Matrix4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+}
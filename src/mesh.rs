@@ -0,0 +1,249 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::{Aabb, surrounding_box};
+use crate::bvh::BvhNode;
+use crate::color::Color;
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, HittableList, Ray};
+use crate::triangle;
+use crate::vec::Point3;
+
+// One triangle of an indexed mesh. Normal and texture coordinate indices are
+// only present when every vertex of the face provides them.
+#[derive(Copy, Clone, Debug)]
+pub struct MeshFace {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+}
+
+// A named run of consecutive faces, e.g. an OBJ `g` or `o` statement.
+#[derive(Clone, Debug)]
+pub struct MeshGroup {
+    pub name: String,
+    pub first_face: usize,
+    pub face_count: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MeshData {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(f64, f64)>,
+    // Per-position vertex colors; empty when the source file has none. Loaded
+    // for callers to use, but not read by `TriangleMesh` when rendering.
+    pub colors: Vec<Color>,
+    pub faces: Vec<MeshFace>,
+    pub groups: Vec<MeshGroup>,
+}
+
+impl MeshData {
+    pub fn new() -> MeshData {
+        MeshData::default()
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        let first = *self.positions.first()?;
+        let bounds = self.positions.iter().fold(Aabb::new(first, first), |acc, p| surrounding_box(&acc, &Aabb::new(*p, *p)));
+        Some(bounds)
+    }
+
+    pub fn translate(&mut self, offset: Vec3) {
+        for p in &mut self.positions {
+            *p = *p + offset;
+        }
+    }
+
+    // Moves the center of the bounding box to the origin.
+    pub fn center(&mut self) {
+        if let Some(bounds) = self.bounds() {
+            self.translate(-bounds.centroid());
+        }
+    }
+
+    // Scales uniformly about the origin so that the longest side of the bounding
+    // box becomes `size`. Uniform scaling leaves vertex normals valid.
+    pub fn scale_to_fit(&mut self, size: f64) {
+        if let Some(bounds) = self.bounds() {
+            let d = bounds.diagonal();
+            let longest = d.x.max(d.y).max(d.z);
+            if longest > 0.0 {
+                let scale = size / longest;
+                for p in &mut self.positions {
+                    *p = *p * scale;
+                }
+            }
+        }
+    }
+
+    fn vertices(&self, face: &MeshFace) -> (Point3, Point3, Point3) {
+        (self.positions[face.positions[0]], self.positions[face.positions[1]], self.positions[face.positions[2]])
+    }
+}
+
+pub struct TriangleMesh {
+    data: Rc<MeshData>,
+    bvh: BvhNode,
+}
+
+impl TriangleMesh {
+    pub fn new(data: MeshData, mat_ptr: Rc<dyn Scatter>) -> TriangleMesh {
+        assert!(!data.faces.is_empty(), "Cannot build a TriangleMesh without faces.");
+        let data = Rc::new(data);
+        let mut triangles = HittableList::with_capacity(data.faces.len());
+        for index in 0..data.faces.len() {
+            triangles.add(Rc::new(MeshTriangle { mesh: Rc::clone(&data), index, mat_ptr: Rc::clone(&mat_ptr) }));
+        }
+        let bvh = BvhNode::new(&triangles, 0.0, 1.0);
+        TriangleMesh { data, bvh }
+    }
+
+    pub fn data(&self) -> &MeshData {
+        &self.data
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.bvh.hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        self.bvh.bounding_box(time0, time1)
+    }
+}
+
+struct MeshTriangle {
+    mesh: Rc<MeshData>,
+    index: usize,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let face = &self.mesh.faces[self.index];
+        let (v0, v1, v2) = self.mesh.vertices(face);
+        let (t, b1, b2) = triangle::intersect(r, v0, v1, v2, t_min, t_max)?;
+        let b0 = 1.0 - b1 - b2;
+
+        // The geometric normal decides which side was hit; interpolated vertex
+        // normals only replace it for shading.
+        let outward_normal = vec::unit_vector(vec::cross(&(v1 - v0), &(v2 - v0)));
+        let mut rec = HitRecord::new(r.at(t), outward_normal, Rc::clone(&self.mat_ptr), t);
+        rec.set_face_normal(r);
+        if let Some([n0, n1, n2]) = face.normals {
+            let normals = &self.mesh.normals;
+            let shading_normal = vec::unit_vector(b0 * normals[n0] + b1 * normals[n1] + b2 * normals[n2]);
+            rec.normal = if rec.front_face { shading_normal } else { -shading_normal };
+        }
+        match face.uvs {
+            Some([t0, t1, t2]) => {
+                let uvs = &self.mesh.uvs;
+                rec.u = b0 * uvs[t0].0 + b1 * uvs[t1].0 + b2 * uvs[t2].0;
+                rec.v = b0 * uvs[t0].1 + b1 * uvs[t1].1 + b2 * uvs[t2].1;
+            }
+            None => {
+                rec.u = b1;
+                rec.v = b2;
+            }
+        }
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        let (v0, v1, v2) = self.mesh.vertices(&self.mesh.faces[self.index]);
+        Some(triangle::triangle_box(v0, v1, v2))
+    }
+}
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io { file: String, source: io::Error },
+    Parse { file: String, line: usize, message: String },
+    // Errors in binary data, where there is no line to point at.
+    Format { file: String, message: String },
+    Empty { file: String },
+}
+
+impl Display for MeshError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MeshError::Io { file, source } => write!(f, "{}: {}", file, source),
+            MeshError::Parse { file, line, message } => write!(f, "{}:{}: {}", file, line, message),
+            MeshError::Format { file, message } => write!(f, "{}: {}", file, message),
+            MeshError::Empty { file } => write!(f, "{}: mesh has no faces", file),
+        }
+    }
+}
+
+impl Error for MeshError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MeshError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::mesh::{MeshData, MeshFace, TriangleMesh};
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    fn quad() -> MeshData {
+        let mut data = MeshData::new();
+        data.positions = vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0),
+                              Point3::new(1.0, 1.0, 0.0), Point3::new(0.0, 1.0, 0.0)];
+        data.uvs = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        data.faces = vec![MeshFace { positions: [0, 1, 2], normals: None, uvs: Some([0, 1, 2]) },
+                          MeshFace { positions: [0, 2, 3], normals: None, uvs: Some([0, 2, 3]) }];
+        data
+    }
+
+    #[test]
+    fn check_hit_interpolates_uvs() {
+        let mesh = TriangleMesh::new(quad(), Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let r = Ray::new(Point3::new(0.25, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(1.0, rec.t, 1e-9);
+        assert_approx_eq!(0.25, rec.u, 1e-9);
+        assert_approx_eq!(0.75, rec.v, 1e-9);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn check_hit_uses_shading_normals() {
+        let mut data = quad();
+        data.normals = vec![Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 1.0) / 2.0_f64.sqrt()];
+        data.faces[0].normals = Some([0, 1, 1]);
+        data.faces[1].normals = Some([0, 1, 0]);
+        let mesh = TriangleMesh::new(data, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let r = Ray::new(Point3::new(0.9, 0.1, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = mesh.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert!(!rec.front_face);
+        assert!(rec.normal.z < 0.0);
+        assert!(rec.normal.x < 0.0);
+    }
+
+    #[test]
+    fn check_center_and_scale_to_fit() {
+        let mut data = quad();
+        data.translate(Vec3::new(2.0, 3.0, 4.0));
+        data.center();
+        data.scale_to_fit(4.0);
+        let bounds = data.bounds().unwrap();
+        assert_eq!(format!("{}", bounds.min()), "-2 -2 0");
+        assert_eq!(format!("{}", bounds.max()), "2 2 0");
+    }
+}
@@ -0,0 +1,215 @@
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::{Aabb, surrounding_box};
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, Ray, sphere_uv};
+use crate::roots::solve_quadratic;
+use crate::vec::Point3;
+
+// Samples taken across the smallest ball's radius while looking for the surface.
+// Features thinner than one step can be missed.
+const STEPS_PER_RADIUS: f64 = 16.0;
+const BISECTION_STEPS: usize = 50;
+
+// A blob of influence around `center`. Its field falls smoothly from `weight` at
+// the center to exactly zero at `radius`, so it cannot affect anything farther away.
+#[derive(Copy, Clone, Debug)]
+pub struct Metaball {
+    center: Point3,
+    radius: f64,
+    weight: f64,
+}
+
+impl Metaball {
+    pub fn new(center: Point3, radius: f64, weight: f64) -> Metaball {
+        assert!(radius > 0.0, "Metaball needs a positive radius.");
+        Metaball { center, radius, weight }
+    }
+
+    // Wyvill's soft-object kernel: weight * (1 - d²/R²)³.
+    fn field(&self, p: Point3) -> f64 {
+        let falloff = 1.0 - (p - self.center).length_squared() / (self.radius * self.radius);
+        if falloff <= 0.0 {
+            0.0
+        } else {
+            self.weight * falloff * falloff * falloff
+        }
+    }
+
+    fn gradient(&self, p: Point3) -> Vec3 {
+        let r2 = self.radius * self.radius;
+        let falloff = 1.0 - (p - self.center).length_squared() / r2;
+        if falloff <= 0.0 {
+            Vec3::default()
+        } else {
+            (-6.0 * self.weight * falloff * falloff / r2) * (p - self.center)
+        }
+    }
+
+    // Parameter range during which the ray is inside the ball's radius of influence.
+    fn span(&self, r: &Ray) -> Option<(f64, f64)> {
+        let oc = r.origin() - self.center;
+        solve_quadratic(r.direction().length_squared(),
+                        2.0 * vec::dot(&oc, &r.direction()),
+                        oc.length_squared() - self.radius * self.radius)
+    }
+}
+
+// Isosurface where the summed field of all balls equals `threshold`. Nearby balls
+// melt into each other; a lone ball of weight 1 shows as a sphere of radius
+// R * sqrt(1 - threshold^(1/3)).
+pub struct Metaballs {
+    balls: Vec<Metaball>,
+    threshold: f64,
+    step: f64,
+    bbox: Aabb,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Metaballs {
+    pub fn new(balls: Vec<Metaball>, threshold: f64, mat_ptr: Rc<dyn Scatter>) -> Metaballs {
+        assert!(!balls.is_empty(), "Metaballs needs at least one ball.");
+        let ball_box = |b: &Metaball| {
+            let radius = Vec3::new(b.radius, b.radius, b.radius);
+            Aabb::new(b.center - radius, b.center + radius)
+        };
+        let bbox = balls.iter().skip(1).fold(ball_box(&balls[0]), |acc, b| surrounding_box(&acc, &ball_box(b)));
+        let step = balls.iter().map(|b| b.radius).fold(f64::INFINITY, f64::min) / STEPS_PER_RADIUS;
+        Metaballs { balls, threshold, step, bbox, mat_ptr }
+    }
+}
+
+impl Hittable for Metaballs {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return None;
+        }
+        // Only the balls whose radius the ray passes through can contribute, and
+        // only inside those spans can the surface be.
+        let mut spans = Vec::new();
+        let mut active = Vec::new();
+        for ball in &self.balls {
+            if let Some((t0, t1)) = ball.span(r) {
+                if t1 >= t_min && t0 <= t_max {
+                    spans.push((t0.max(t_min), t1.min(t_max)));
+                    active.push(*ball);
+                }
+            }
+        }
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let excess = |t: f64| {
+            let p = r.at(t);
+            active.iter().map(|b| b.field(p)).sum::<f64>() - self.threshold
+        };
+        let dt = self.step / r.direction().length();
+        let mut merged_end = f64::NEG_INFINITY;
+        for (start, end) in spans {
+            // Skip the part already marched as part of an overlapping span.
+            let start = start.max(merged_end);
+            if start >= end {
+                continue;
+            }
+            merged_end = end;
+            let (mut t0, mut f0) = (start, excess(start));
+            while t0 < end {
+                let t1 = (t0 + dt).min(end);
+                let f1 = excess(t1);
+                if (f0 > 0.0) != (f1 > 0.0) {
+                    let (mut lo, mut hi) = (t0, t1);
+                    for _ in 0..BISECTION_STEPS {
+                        let mid = 0.5 * (lo + hi);
+                        if (excess(mid) > 0.0) == (f0 > 0.0) {
+                            lo = mid;
+                        } else {
+                            hi = mid;
+                        }
+                    }
+                    let t = 0.5 * (lo + hi);
+                    let p = r.at(t);
+                    // The field grows toward the centers, so the outward normal is
+                    // against the gradient.
+                    let gradient = active.iter().fold(Vec3::default(), |acc, b| acc + b.gradient(p));
+                    let outward_normal = vec::unit_vector(-gradient);
+                    let mut rec = HitRecord::new(p, outward_normal, Rc::clone(&self.mat_ptr), t);
+                    rec.set_face_normal(r);
+                    (rec.u, rec.v) = sphere_uv(outward_normal);
+                    return Some(rec);
+                }
+                t0 = t1;
+                f0 = f1;
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::metaballs::{Metaball, Metaballs};
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    fn blobs(balls: Vec<Metaball>) -> Metaballs {
+        Metaballs::new(balls, 0.125, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_single_ball_is_a_sphere() {
+        // With threshold 1/8 the surface sits where (1 - d²/4)³ = 1/8, at d = sqrt(2).
+        let blob = blobs(vec![Metaball::new(Point3::new(0.0, 0.0, 0.0), 2.0, 1.0)]);
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = blob.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(5.0 - 2.0_f64.sqrt(), rec.t, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.z, 1e-9);
+        assert!(rec.front_face);
+
+        let inside = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = blob.hit(&inside, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(2.0_f64.sqrt(), rec.t, 1e-9);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn check_balls_merge() {
+        // Each ball alone reaches sqrt(2) < 1.5 from its center, but together they
+        // bridge the gap between them.
+        let balls = vec![Metaball::new(Point3::new(-1.5, 0.0, 0.0), 2.0, 1.0),
+                         Metaball::new(Point3::new(1.5, 0.0, 0.0), 2.0, 1.0)];
+        let r = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = blobs(balls).hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert!(rec.t > 4.0 && rec.t < 5.0);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-9);
+
+        let far_apart = vec![Metaball::new(Point3::new(-5.0, 0.0, 0.0), 2.0, 1.0),
+                             Metaball::new(Point3::new(5.0, 0.0, 0.0), 2.0, 1.0)];
+        assert!(blobs(far_apart).hit(&r, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_negative_weight_carves() {
+        let balls = vec![Metaball::new(Point3::new(0.0, 0.0, 0.0), 2.0, 1.0),
+                         Metaball::new(Point3::new(0.0, 0.0, 1.5), 1.0, -1.0)];
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        // The negative ball eats into the sphere that would otherwise show at t = 5 - sqrt(2).
+        let rec = blobs(balls).hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert!(rec.t > 5.0 - 2.0_f64.sqrt() + 0.1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Metaball needs a positive radius.")]
+    fn check_zero_radius_is_rejected() {
+        Metaball::new(Point3::new(0.0, 0.0, 0.0), 0.0, 1.0);
+    }
+}
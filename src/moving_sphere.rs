@@ -0,0 +1,113 @@
+use std::rc::Rc;
+
+use crate::Vec3;
+use crate::aabb::{Aabb, surrounding_box};
+use crate::material::Scatter;
+use crate::raytracing::{hit_sphere, HitRecord, Hittable, Ray};
+use crate::vec::Point3;
+
+// Sphere whose center follows a piecewise linear path through keyframes of
+// (time, center). Before the first and after the last keyframe it stays put.
+pub struct MovingSphere {
+    keyframes: Vec<(f64, Point3)>,
+    radius: f64,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl MovingSphere {
+    // Moves linearly from `center0` at `time0` to `center1` at `time1`.
+    pub fn new(center0: Point3, center1: Point3, time0: f64, time1: f64, radius: f64, mat_ptr: Rc<dyn Scatter>) -> MovingSphere {
+        MovingSphere::keyframed(vec![(time0, center0), (time1, center1)], radius, mat_ptr)
+    }
+
+    pub fn keyframed(mut keyframes: Vec<(f64, Point3)>, radius: f64, mat_ptr: Rc<dyn Scatter>) -> MovingSphere {
+        assert!(!keyframes.is_empty(), "MovingSphere needs at least one keyframe.");
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        MovingSphere { keyframes, radius, mat_ptr }
+    }
+
+    pub fn center(&self, time: f64) -> Point3 {
+        let next = self.keyframes.partition_point(|&(t, _)| t <= time);
+        if next == 0 {
+            return self.keyframes[0].1;
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes[next - 1].1;
+        }
+        let (time0, center0) = self.keyframes[next - 1];
+        let (time1, center1) = self.keyframes[next];
+        center0 + ((time - time0) / (time1 - time0)) * (center1 - center0)
+    }
+
+    fn sphere_box(&self, center: Point3) -> Aabb {
+        let radius = Vec3::new(self.radius.abs(), self.radius.abs(), self.radius.abs());
+        Aabb::new(center - radius, center + radius)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        hit_sphere(self.center(r.time()), self.radius, &self.mat_ptr, r, t_min, t_max)
+    }
+
+    // The path is linear between keyframes, so the boxes at the interval ends and
+    // at every keyframe inside it enclose the whole sweep.
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let mut output_box = surrounding_box(&self.sphere_box(self.center(time0)), &self.sphere_box(self.center(time1)));
+        for &(time, center) in &self.keyframes {
+            if time > time0 && time < time1 {
+                output_box = surrounding_box(&output_box, &self.sphere_box(center));
+            }
+        }
+        Some(output_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::moving_sphere::MovingSphere;
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    fn bouncing() -> MovingSphere {
+        MovingSphere::keyframed(vec![(0.0, Point3::new(0.0, 0.0, 0.0)),
+                                     (1.0, Point3::new(0.0, 2.0, 0.0)),
+                                     (0.5, Point3::new(4.0, 1.0, 0.0))],
+                                0.5, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_center_interpolation() {
+        let sphere = bouncing();
+        assert_eq!(format!("{}", sphere.center(-1.0)), "0 0 0");
+        assert_eq!(format!("{}", sphere.center(0.25)), "2 0.5 0");
+        assert_eq!(format!("{}", sphere.center(0.75)), "2 1.5 0");
+        assert_eq!(format!("{}", sphere.center(3.0)), "0 2 0");
+    }
+
+    #[test]
+    fn check_hit_depends_on_time() {
+        let sphere = bouncing();
+        let early = Ray::with_time(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let rec = sphere.hit(&early, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.5, rec.t, 1e-9);
+        let late = Ray::with_time(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.5);
+        assert!(sphere.hit(&late, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_bounding_box_covers_keyframes() {
+        let bbox = bouncing().bounding_box(0.0, 1.0).unwrap();
+        assert_approx_eq!(4.5, bbox.max().x, 1e-9);
+        assert_approx_eq!(2.5, bbox.max().y, 1e-9);
+        assert_approx_eq!(-0.5, bbox.min().y, 1e-9);
+        let bbox = bouncing().bounding_box(0.0, 0.25).unwrap();
+        assert_approx_eq!(2.5, bbox.max().x, 1e-9);
+    }
+}
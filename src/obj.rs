@@ -0,0 +1,189 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::rc::Rc;
+
+use crate::Vec3;
+use crate::material::Scatter;
+use crate::mesh::{MeshData, MeshError, MeshFace, MeshGroup, TriangleMesh};
+use crate::vec::Point3;
+
+pub fn load_obj(path: &str, mat_ptr: Rc<dyn Scatter>) -> Result<TriangleMesh, MeshError> {
+    let file = File::open(path).map_err(|source| MeshError::Io { file: path.to_string(), source })?;
+    let data = parse_obj(BufReader::new(file), path)?;
+    Ok(TriangleMesh::new(data, mat_ptr))
+}
+
+// Reads positions, normals, texture coordinates, faces and groups from Wavefront
+// OBJ text. Polygons are triangulated as fans around their first vertex; other
+// statements such as `usemtl` or `s` are ignored. `file` is only used for diagnostics.
+pub fn parse_obj<R: BufRead>(reader: R, file: &str) -> Result<MeshData, MeshError> {
+    let mut data = MeshData::new();
+    let mut group = MeshGroup { name: String::from("default"), first_face: 0, face_count: 0 };
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.map_err(|source| MeshError::Io { file: file.to_string(), source })?;
+        let error = |message: String| MeshError::Parse { file: file.to_string(), line: line_number, message };
+
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats::<3>(&args, 3, 4).map_err(error)?;
+                data.positions.push(Point3::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats::<3>(&args, 3, 3).map_err(error)?;
+                data.normals.push(Vec3::new(x, y, z));
+            }
+            "vt" => {
+                let [u, v] = parse_floats::<2>(&args, 1, 3).map_err(error)?;
+                data.uvs.push((u, v));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error(format!("face needs at least 3 vertices, found {}", args.len())));
+                }
+                let vertices = args.iter()
+                    .map(|arg| parse_face_vertex(arg, &data))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                for i in 1..vertices.len() - 1 {
+                    let (a, b, c) = (vertices[0], vertices[i], vertices[i + 1]);
+                    data.faces.push(MeshFace {
+                        positions: [a.0, b.0, c.0],
+                        uvs: a.1.zip(b.1).zip(c.1).map(|((a, b), c)| [a, b, c]),
+                        normals: a.2.zip(b.2).zip(c.2).map(|((a, b), c)| [a, b, c]),
+                    });
+                    group.face_count += 1;
+                }
+            }
+            "g" | "o" => {
+                let name = if args.is_empty() { String::from("default") } else { args.join(" ") };
+                let next = MeshGroup { name, first_face: data.faces.len(), face_count: 0 };
+                let finished = std::mem::replace(&mut group, next);
+                if finished.face_count > 0 {
+                    data.groups.push(finished);
+                }
+            }
+            _ => {}
+        }
+    }
+    if group.face_count > 0 {
+        data.groups.push(group);
+    }
+
+    if data.faces.is_empty() {
+        return Err(MeshError::Empty { file: file.to_string() });
+    }
+    Ok(data)
+}
+
+// Parses between `min` and `max` numbers; missing trailing values default to 0
+// and values beyond the first N are accepted but dropped.
+fn parse_floats<const N: usize>(args: &[&str], min: usize, max: usize) -> Result<[f64; N], String> {
+    if args.len() < min || args.len() > max {
+        return Err(format!("expected {} to {} numbers, found {}", min, max, args.len()));
+    }
+    let mut values = [0.0; N];
+    for (i, arg) in args.iter().enumerate() {
+        let value: f64 = arg.parse().map_err(|_| format!("invalid number '{}'", arg))?;
+        if i < N {
+            values[i] = value;
+        }
+    }
+    Ok(values)
+}
+
+type FaceVertex = (usize, Option<usize>, Option<usize>);
+
+// Parses `v`, `v/vt`, `v//vn` or `v/vt/vn` into zero-based indices.
+fn parse_face_vertex(arg: &str, data: &MeshData) -> Result<FaceVertex, String> {
+    let mut parts = arg.split('/');
+    let position = resolve_index(parts.next().unwrap_or(""), data.positions.len(), "vertex")?;
+    let uv = match parts.next() {
+        Some("") | None => None,
+        Some(s) => Some(resolve_index(s, data.uvs.len(), "texture coordinate")?),
+    };
+    let normal = match parts.next() {
+        Some("") | None => None,
+        Some(s) => Some(resolve_index(s, data.normals.len(), "normal")?),
+    };
+    if parts.next().is_some() {
+        return Err(format!("invalid face vertex '{}'", arg));
+    }
+    Ok((position, uv, normal))
+}
+
+// OBJ indices are one-based; negative indices count back from the last element read so far.
+fn resolve_index(s: &str, count: usize, kind: &str) -> Result<usize, String> {
+    let index: i64 = s.parse().map_err(|_| format!("invalid {} index '{}'", kind, s))?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!("{} index {} out of range (have {})", kind, index, count));
+    }
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::mesh::MeshError;
+    use crate::obj::parse_obj;
+
+    #[test]
+    fn check_parse_quad_is_triangulated() {
+        let source = "# a unit quad\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n";
+        let data = parse_obj(source.as_bytes(), "quad.obj").unwrap();
+        assert_eq!(data.positions.len(), 4);
+        assert_eq!(data.faces.len(), 2);
+        assert_eq!(data.faces[0].positions, [0, 1, 2]);
+        assert_eq!(data.faces[1].positions, [0, 2, 3]);
+        assert!(data.faces[0].normals.is_none());
+    }
+
+    #[test]
+    fn check_parse_normals_uvs_and_negative_indices() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\nf -3/1/1 -2/2/1 -1/3/1\n";
+        let data = parse_obj(source.as_bytes(), "tri.obj").unwrap();
+        assert_eq!(data.faces[0].positions, [0, 1, 2]);
+        assert_eq!(data.faces[0].uvs, Some([0, 1, 2]));
+        assert_eq!(data.faces[0].normals, Some([0, 0, 0]));
+        assert_approx_eq!(1.0, data.uvs[1].0, 1e-9);
+    }
+
+    #[test]
+    fn check_parse_groups() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\ng first\nf 1 2 3\ng second part\nf 1 3 2\nf 2 3 1\n";
+        let data = parse_obj(source.as_bytes(), "groups.obj").unwrap();
+        assert_eq!(data.groups.len(), 2);
+        assert_eq!(data.groups[1].name, "second part");
+        assert_eq!(data.groups[1].first_face, 1);
+        assert_eq!(data.groups[1].face_count, 2);
+    }
+
+    #[test]
+    fn check_malformed_line_reports_location() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 x 0\nf 1 2 3\n";
+        let error = parse_obj(source.as_bytes(), "bad.obj").unwrap_err();
+        assert_eq!(format!("{}", error), "bad.obj:4: invalid number 'x'");
+
+        let source = "v 0 0 0\nf 1 2 3\n";
+        let error = parse_obj(source.as_bytes(), "bad.obj").unwrap_err();
+        assert_eq!(format!("{}", error), "bad.obj:2: vertex index 2 out of range (have 1)");
+
+        let error = parse_obj("v 0 0 0\n".as_bytes(), "empty.obj").unwrap_err();
+        assert!(matches!(error, MeshError::Empty { .. }));
+    }
+}
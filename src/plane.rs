@@ -0,0 +1,148 @@
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::Aabb;
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::vec::Point3;
+
+// Ray parameter of the intersection with the plane through `point` with unit `normal`.
+fn intersect_plane(r: &Ray, point: Point3, normal: Vec3, t_min: f64, t_max: f64) -> Option<f64> {
+    let denom = vec::dot(&normal, &r.direction());
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let t = vec::dot(&(point - r.origin()), &normal) / denom;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    Some(t)
+}
+
+// Tight box around a disk with unit `normal`. Along each axis the disk extends
+// radius * sin(angle between the axis and the normal).
+pub fn disk_box(center: Point3, normal: Vec3, radius: f64) -> Aabb {
+    let n = normal;
+    let extent = radius * Vec3::new((1.0 - n.x * n.x).max(0.0).sqrt(),
+                                    (1.0 - n.y * n.y).max(0.0).sqrt(),
+                                    (1.0 - n.z * n.z).max(0.0).sqrt());
+    Aabb::new(center - extent, center + extent)
+}
+
+// Infinite plane. It has no bounding box, so it has to be kept out of a BvhNode
+// and added next to it in a HittableList instead.
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, mat_ptr: Rc<dyn Scatter>) -> Plane {
+        let normal = vec::unit_vector(normal);
+        let (tangent, bitangent) = vec::tangent_frame(normal);
+        Plane { point, normal, tangent, bitangent, mat_ptr }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = intersect_plane(r, self.point, self.normal, t_min, t_max)?;
+        let p = r.at(t);
+        let mut rec = HitRecord::new(p, self.normal, Rc::clone(&self.mat_ptr), t);
+        // Unbounded planar coordinates in world units, so textures tile across the plane.
+        rec.u = vec::dot(&(p - self.point), &self.tangent);
+        rec.v = vec::dot(&(p - self.point), &self.bitangent);
+        rec.set_face_normal(r);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        None
+    }
+}
+
+pub struct Disk {
+    center: Point3,
+    normal: Vec3,
+    radius: f64,
+    tangent: Vec3,
+    bitangent: Vec3,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, mat_ptr: Rc<dyn Scatter>) -> Disk {
+        let normal = vec::unit_vector(normal);
+        let (tangent, bitangent) = vec::tangent_frame(normal);
+        Disk { center, normal, radius, tangent, bitangent, mat_ptr }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = intersect_plane(r, self.center, self.normal, t_min, t_max)?;
+        let p = r.at(t);
+        let offset = p - self.center;
+        if offset.length_squared() > self.radius * self.radius {
+            return None;
+        }
+        let mut rec = HitRecord::new(p, self.normal, Rc::clone(&self.mat_ptr), t);
+        // Polar coordinates: u is the angle around the normal, v the distance from the center.
+        let phi = f64::atan2(vec::dot(&offset, &self.bitangent), vec::dot(&offset, &self.tangent));
+        rec.u = (phi + PI) / (2.0 * PI);
+        rec.v = offset.length() / self.radius;
+        rec.set_face_normal(r);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(disk_box(self.center, self.normal, self.radius).pad(0.0001))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::plane::{Disk, Plane};
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    #[test]
+    fn check_plane_hit() {
+        let plane = Plane::new(Point3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 2.0, 0.0),
+                               Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let r = Ray::new(Point3::new(100.0, 1.0, -50.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = plane.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(2.0, rec.t, 1e-9);
+        assert_approx_eq!(-1.0, rec.p.y, 1e-9);
+        assert!(rec.front_face);
+        let up = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        assert!(plane.hit(&up, 0.0, f64::INFINITY).is_none());
+        assert!(plane.bounding_box(0.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn check_disk_hit() {
+        let disk = Disk::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 2.0,
+                             Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let inside = Ray::new(Point3::new(1.0, 0.0, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = disk.hit(&inside, 0.0, f64::INFINITY).unwrap();
+        assert!(!rec.front_face);
+        assert_approx_eq!(0.5, rec.v, 1e-9);
+        let outside = Ray::new(Point3::new(1.5, 1.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        assert!(disk.hit(&outside, 0.0, f64::INFINITY).is_none());
+
+        let bbox = disk.bounding_box(0.0, 1.0).unwrap();
+        assert_approx_eq!(2.0, bbox.max().x, 1e-9);
+        assert!(bbox.max().z < 0.001);
+    }
+}
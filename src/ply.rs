@@ -0,0 +1,464 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::rc::Rc;
+
+use crate::Vec3;
+use crate::bvh::BvhNode;
+use crate::color::Color;
+use crate::material::Scatter;
+use crate::mesh::{MeshData, MeshError, MeshFace, TriangleMesh};
+use crate::raytracing::{Hittable, HittableList, Sphere};
+use crate::vec::Point3;
+
+// Loads a PLY file as a triangle mesh, or as a cloud of spheres of `point_radius`
+// when the file has vertices but no faces. Vertex colors are only loaded into
+// `MeshData::colors` by `parse_ply`; the returned geometry renders with `mat_ptr`.
+pub fn load_ply(path: &str, mat_ptr: Rc<dyn Scatter>, point_radius: f64) -> Result<Rc<dyn Hittable>, MeshError> {
+    let file = File::open(path).map_err(|source| MeshError::Io { file: path.to_string(), source })?;
+    let data = parse_ply(BufReader::new(file), path)?;
+    if data.faces.is_empty() {
+        Ok(Rc::new(point_cloud(&data, point_radius, mat_ptr)))
+    } else {
+        Ok(Rc::new(TriangleMesh::new(data, mat_ptr)))
+    }
+}
+
+pub fn point_cloud(data: &MeshData, radius: f64, mat_ptr: Rc<dyn Scatter>) -> BvhNode {
+    let mut points = HittableList::with_capacity(data.positions.len());
+    for p in &data.positions {
+        points.add(Rc::new(Sphere::new(*p, radius, Rc::clone(&mat_ptr))));
+    }
+    BvhNode::new(&points, 0.0, 1.0)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<ScalarType> {
+        match name {
+            "char" | "int8" => Some(ScalarType::Int8),
+            "uchar" | "uint8" => Some(ScalarType::UInt8),
+            "short" | "int16" => Some(ScalarType::Int16),
+            "ushort" | "uint16" => Some(ScalarType::UInt16),
+            "int" | "int32" => Some(ScalarType::Int32),
+            "uint" | "uint32" => Some(ScalarType::UInt32),
+            "float" | "float32" => Some(ScalarType::Float32),
+            "double" | "float64" => Some(ScalarType::Float64),
+            _ => None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        }
+    }
+
+    // Scale that maps an integer color channel's largest value to 1. Floating
+    // point channels are already in [0, 1].
+    fn color_scale(&self) -> f64 {
+        match self {
+            ScalarType::Int8 => 1.0 / i8::MAX as f64,
+            ScalarType::UInt8 => 1.0 / u8::MAX as f64,
+            ScalarType::Int16 => 1.0 / i16::MAX as f64,
+            ScalarType::UInt16 => 1.0 / u16::MAX as f64,
+            ScalarType::Int32 => 1.0 / i32::MAX as f64,
+            ScalarType::UInt32 => 1.0 / u32::MAX as f64,
+            ScalarType::Float32 | ScalarType::Float64 => 1.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum PropertyKind {
+    Scalar(ScalarType),
+    List(ScalarType, ScalarType),
+}
+
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+pub fn parse_ply<R: BufRead>(mut reader: R, file: &str) -> Result<MeshData, MeshError> {
+    let (format, elements, header_lines) = parse_header(&mut reader, file)?;
+    let mut body = BodyReader { reader, format, file, line: header_lines, tokens: VecDeque::new() };
+    let mut data = MeshData::new();
+
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => read_vertices(&mut body, element, &mut data)?,
+            "face" => read_faces(&mut body, element, &mut data)?,
+            _ => {
+                for _ in 0..element.count {
+                    body.begin_element()?;
+                    for property in &element.properties {
+                        body.read_property(property.kind)?;
+                    }
+                    body.end_element()?;
+                }
+            }
+        }
+    }
+
+    // Normals and texture coordinates are stored per vertex in PLY. They are
+    // attached once every element is read, since faces may come before vertices.
+    for face in &mut data.faces {
+        face.normals = if data.normals.is_empty() { None } else { Some(face.positions) };
+        face.uvs = if data.uvs.is_empty() { None } else { Some(face.positions) };
+    }
+
+    let vertex_count = data.positions.len();
+    if let Some(face) = data.faces.iter().find(|face| face.positions.iter().any(|&i| i >= vertex_count)) {
+        return Err(MeshError::Format {
+            file: file.to_string(),
+            message: format!("face references vertex {:?} but only {} vertices exist", face.positions, vertex_count),
+        });
+    }
+    if vertex_count == 0 {
+        return Err(MeshError::Empty { file: file.to_string() });
+    }
+    Ok(data)
+}
+
+fn parse_header<R: BufRead>(reader: &mut R, file: &str) -> Result<(PlyFormat, Vec<Element>, usize), MeshError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut line_number = 0;
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).map_err(|source| MeshError::Io { file: file.to_string(), source })?;
+        line_number += 1;
+        let error = |message: String| MeshError::Parse { file: file.to_string(), line: line_number, message };
+        if read == 0 {
+            return Err(error(String::from("unexpected end of file in header")));
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if line_number == 1 {
+            if tokens != ["ply"] {
+                return Err(error(String::from("missing 'ply' magic number")));
+            }
+            continue;
+        }
+        match tokens.as_slice() {
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(error(format!("unknown format '{}'", name))),
+                });
+            }
+            ["element", name, count] => {
+                let count = count.parse().map_err(|_| error(format!("invalid element count '{}'", count)))?;
+                elements.push(Element { name: name.to_string(), count, properties: Vec::new() });
+            }
+            ["property", "list", count_type, item_type, name] => {
+                let count_type = ScalarType::parse(count_type).ok_or_else(|| error(format!("unknown type '{}'", count_type)))?;
+                let item_type = ScalarType::parse(item_type).ok_or_else(|| error(format!("unknown type '{}'", item_type)))?;
+                let element = elements.last_mut().ok_or_else(|| error(String::from("property before any element")))?;
+                element.properties.push(Property { name: name.to_string(), kind: PropertyKind::List(count_type, item_type) });
+            }
+            ["property", ty, name] => {
+                let ty = ScalarType::parse(ty).ok_or_else(|| error(format!("unknown type '{}'", ty)))?;
+                let element = elements.last_mut().ok_or_else(|| error(String::from("property before any element")))?;
+                element.properties.push(Property { name: name.to_string(), kind: PropertyKind::Scalar(ty) });
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(error(format!("unexpected header line '{}'", line.trim()))),
+        }
+    }
+    let format = format.ok_or_else(|| MeshError::Parse {
+        file: file.to_string(),
+        line: line_number,
+        message: String::from("missing format line"),
+    })?;
+    Ok((format, elements, line_number))
+}
+
+fn read_vertices<R: BufRead>(body: &mut BodyReader<R>, element: &Element, data: &mut MeshData) -> Result<(), MeshError> {
+    let has = |names: &[&str]| element.properties.iter().any(|p| names.contains(&p.name.as_str()));
+    let has_normals = has(&["nx"]) && has(&["ny"]) && has(&["nz"]);
+    let has_colors = has(&["red", "diffuse_red"]) && has(&["green", "diffuse_green"]) && has(&["blue", "diffuse_blue"]);
+    let has_uvs = has(&["u", "s", "texture_u"]) && has(&["v", "t", "texture_v"]);
+
+    for _ in 0..element.count {
+        body.begin_element()?;
+        let mut position = Point3::default();
+        let mut normal = Vec3::default();
+        let mut color = Color::default();
+        let mut uv = (0.0, 0.0);
+        for property in &element.properties {
+            let values = body.read_property(property.kind)?;
+            let value = values.first().copied().unwrap_or(0.0);
+            let color_scale = match property.kind {
+                PropertyKind::Scalar(ty) => ty.color_scale(),
+                PropertyKind::List(..) => 1.0,
+            };
+            match property.name.as_str() {
+                "x" => position.x = value,
+                "y" => position.y = value,
+                "z" => position.z = value,
+                "nx" => normal.x = value,
+                "ny" => normal.y = value,
+                "nz" => normal.z = value,
+                "red" | "diffuse_red" => color.x = value * color_scale,
+                "green" | "diffuse_green" => color.y = value * color_scale,
+                "blue" | "diffuse_blue" => color.z = value * color_scale,
+                "u" | "s" | "texture_u" => uv.0 = value,
+                "v" | "t" | "texture_v" => uv.1 = value,
+                _ => {}
+            }
+        }
+        body.end_element()?;
+        data.positions.push(position);
+        if has_normals {
+            data.normals.push(normal);
+        }
+        if has_colors {
+            data.colors.push(color);
+        }
+        if has_uvs {
+            data.uvs.push(uv);
+        }
+    }
+    Ok(())
+}
+
+fn read_faces<R: BufRead>(body: &mut BodyReader<R>, element: &Element, data: &mut MeshData) -> Result<(), MeshError> {
+    for _ in 0..element.count {
+        body.begin_element()?;
+        let mut indices = Vec::new();
+        for property in &element.properties {
+            let values = body.read_property(property.kind)?;
+            if property.name == "vertex_indices" || property.name == "vertex_index" {
+                indices = values;
+            }
+        }
+        body.end_element()?;
+        if indices.len() < 3 {
+            return Err(body.error(format!("face needs at least 3 vertices, found {}", indices.len())));
+        }
+        if let Some(index) = indices.iter().find(|&&i| i < 0.0) {
+            return Err(body.error(format!("negative vertex index {}", index)));
+        }
+        for i in 1..indices.len() - 1 {
+            let positions = [indices[0] as usize, indices[i] as usize, indices[i + 1] as usize];
+            data.faces.push(MeshFace { positions, normals: None, uvs: None });
+        }
+    }
+    Ok(())
+}
+
+struct BodyReader<'a, R: BufRead> {
+    reader: R,
+    format: PlyFormat,
+    file: &'a str,
+    line: usize,
+    // Remaining values of the current line in ascii files.
+    tokens: VecDeque<String>,
+}
+
+impl<'a, R: BufRead> BodyReader<'a, R> {
+    fn error(&self, message: String) -> MeshError {
+        match self.format {
+            PlyFormat::Ascii => MeshError::Parse { file: self.file.to_string(), line: self.line, message },
+            _ => MeshError::Format { file: self.file.to_string(), message },
+        }
+    }
+
+    // Ascii files store one element per line.
+    fn begin_element(&mut self) -> Result<(), MeshError> {
+        if self.format != PlyFormat::Ascii {
+            return Ok(());
+        }
+        loop {
+            let mut line = String::new();
+            let read = self.reader.read_line(&mut line)
+                .map_err(|source| MeshError::Io { file: self.file.to_string(), source })?;
+            self.line += 1;
+            if read == 0 {
+                return Err(self.error(String::from("unexpected end of file")));
+            }
+            self.tokens = line.split_whitespace().map(String::from).collect();
+            if !self.tokens.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn end_element(&mut self) -> Result<(), MeshError> {
+        if !self.tokens.is_empty() {
+            return Err(self.error(format!("{} unexpected trailing values", self.tokens.len())));
+        }
+        Ok(())
+    }
+
+    fn read_property(&mut self, kind: PropertyKind) -> Result<Vec<f64>, MeshError> {
+        match kind {
+            PropertyKind::Scalar(ty) => Ok(vec![self.read_scalar(ty)?]),
+            PropertyKind::List(count_type, item_type) => {
+                let count = self.read_scalar(count_type)?;
+                if count < 0.0 {
+                    return Err(self.error(format!("negative list length {}", count)));
+                }
+                (0..count as usize).map(|_| self.read_scalar(item_type)).collect()
+            }
+        }
+    }
+
+    fn read_scalar(&mut self, ty: ScalarType) -> Result<f64, MeshError> {
+        if self.format == PlyFormat::Ascii {
+            let token = self.tokens.pop_front().ok_or_else(|| self.error(String::from("too few values")))?;
+            return token.parse().map_err(|_| self.error(format!("invalid number '{}'", token)));
+        }
+
+        let mut buf = [0u8; 8];
+        let bytes = &mut buf[..ty.size()];
+        self.reader.read_exact(bytes).map_err(|source| match source.kind() {
+            ErrorKind::UnexpectedEof => self.error(String::from("unexpected end of binary data")),
+            _ => MeshError::Io { file: self.file.to_string(), source },
+        })?;
+        if self.format == PlyFormat::BinaryBigEndian {
+            bytes.reverse();
+        }
+        Ok(match ty {
+            ScalarType::Int8 => i8::from_le_bytes([buf[0]]) as f64,
+            ScalarType::UInt8 => buf[0] as f64,
+            ScalarType::Int16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+            ScalarType::UInt16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+            ScalarType::Int32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::UInt32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::Float32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            ScalarType::Float64 => f64::from_le_bytes(buf),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::mesh::MeshError;
+    use crate::ply::parse_ply;
+
+    const HEADER: &str = "element vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+                          property uchar red\nproperty uchar green\nproperty uchar blue\n\
+                          element face 1\nproperty list uchar int vertex_indices\nend_header\n";
+
+    #[test]
+    fn check_parse_ascii() {
+        let source = format!("ply\nformat ascii 1.0\ncomment quad\n{}\
+                              0 0 0 255 0 0\n1 0 0 0 255 0\n1 1 0 0 0 255\n0 1 0 255 255 255\n4 0 1 2 3\n", HEADER);
+        let data = parse_ply(source.as_bytes(), "quad.ply").unwrap();
+        assert_eq!(data.positions.len(), 4);
+        assert_eq!(data.faces.len(), 2);
+        assert_eq!(data.faces[1].positions, [0, 2, 3]);
+        assert_approx_eq!(1.0, data.colors[1].y, 1e-9);
+        assert!(data.faces[0].normals.is_none());
+    }
+
+    fn binary_body(big_endian: bool) -> Vec<u8> {
+        let mut body = Vec::new();
+        let vertices = [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+        for v in vertices {
+            for c in v {
+                body.extend_from_slice(&if big_endian { c.to_be_bytes() } else { c.to_le_bytes() });
+            }
+            body.extend_from_slice(&[10, 20, 30]);
+        }
+        body.push(3);
+        for i in [0i32, 1, 2] {
+            body.extend_from_slice(&if big_endian { i.to_be_bytes() } else { i.to_le_bytes() });
+        }
+        body
+    }
+
+    #[test]
+    fn check_parse_binary() {
+        for (format, big_endian) in [("binary_little_endian", false), ("binary_big_endian", true)] {
+            let mut source = format!("ply\nformat {} 1.0\n{}", format, HEADER).into_bytes();
+            source.extend(binary_body(big_endian));
+            let data = parse_ply(source.as_slice(), "quad.ply").unwrap();
+            assert_approx_eq!(1.0, data.positions[2].y, 1e-9);
+            assert_eq!(data.faces.len(), 1);
+            assert_eq!(data.faces[0].positions, [0, 1, 2]);
+            assert_approx_eq!(20.0 / 255.0, data.colors[3].y, 1e-9);
+        }
+    }
+
+    #[test]
+    fn check_color_scales() {
+        let source = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\n\
+                      property short red\nproperty uint green\nproperty float blue\nend_header\n0 0 0 16383 4294967295 0.5\n";
+        let data = parse_ply(source.as_bytes(), "colors.ply").unwrap();
+        assert_approx_eq!(16383.0 / 32767.0, data.colors[0].x, 1e-9);
+        assert_approx_eq!(1.0, data.colors[0].y, 1e-9);
+        assert_approx_eq!(0.5, data.colors[0].z, 1e-9);
+    }
+
+    #[test]
+    fn check_faces_before_vertices() {
+        let source = "ply\nformat ascii 1.0\nelement face 1\nproperty list uchar int vertex_indices\n\
+                      element vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                      property float nx\nproperty float ny\nproperty float nz\nproperty float u\nproperty float v\n\
+                      end_header\n3 0 1 2\n0 0 0 0 0 1 0 0\n1 0 0 0 0 1 1 0\n0 1 0 0 0 1 0 1\n";
+        let data = parse_ply(source.as_bytes(), "tri.ply").unwrap();
+        assert_eq!(data.faces[0].normals, Some([0, 1, 2]));
+        assert_eq!(data.faces[0].uvs, Some([0, 1, 2]));
+        assert_approx_eq!(1.0, data.normals[2].z, 1e-9);
+    }
+
+    #[test]
+    fn check_parse_point_cloud() {
+        let source = "ply\nformat ascii 1.0\nelement vertex 2\nproperty double x\nproperty double y\n\
+                      property double z\nend_header\n0 0 0\n1 2 3\n";
+        let data = parse_ply(source.as_bytes(), "points.ply").unwrap();
+        assert_eq!(data.positions.len(), 2);
+        assert!(data.faces.is_empty());
+        assert!(data.colors.is_empty());
+    }
+
+    #[test]
+    fn check_malformed_data_reports_location() {
+        let source = format!("ply\nformat ascii 1.0\n{}0 0 0 1 1 1\n1 0 0 1 1 1\n1 x 0 1 1 1\n", HEADER);
+        let error = parse_ply(source.as_bytes(), "bad.ply").unwrap_err();
+        assert_eq!(format!("{}", error), "bad.ply:15: invalid number 'x'");
+
+        let mut source = format!("ply\nformat binary_little_endian 1.0\n{}", HEADER).into_bytes();
+        source.extend(&binary_body(false)[..20]);
+        let error = parse_ply(source.as_slice(), "short.ply").unwrap_err();
+        assert!(matches!(error, MeshError::Format { .. }));
+
+        let error = parse_ply("obj\n".as_bytes(), "wrong.ply").unwrap_err();
+        assert_eq!(format!("{}", error), "wrong.ply:1: missing 'ply' magic number");
+    }
+}
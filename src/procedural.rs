@@ -0,0 +1,311 @@
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::color::Color;
+use crate::perlin::{splitmix64, unit};
+use crate::texture::{lerp, SolidColor, Texture};
+use crate::vec::Point3;
+
+// What a Worley texture shows of the cell pattern.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CellularMode {
+    // Distance to the nearest feature point (F1): dark spots growing brighter outward.
+    Distance,
+    // Gap between the two nearest distances (F2 - F1): dark lines along cell borders.
+    Edges,
+    // A flat random color per cell, like crazy paving or stained glass.
+    Cells,
+}
+
+// Cellular noise: one random feature point per unit cube of space, with the
+// pattern read from the distances to the nearest of them. Grey values are
+// clamped to [0, 1]; the same seed always gives the same cells.
+pub struct Worley {
+    scale: f64,
+    mode: CellularMode,
+    seed: u64,
+}
+
+impl Worley {
+    pub fn new(scale: f64, mode: CellularMode, seed: u64) -> Worley {
+        Worley { scale, mode, seed }
+    }
+
+    // Feature point of cell (i, j, k) and the hash it was made from.
+    fn feature(&self, i: i64, j: i64, k: i64) -> (Point3, u64) {
+        let hash = splitmix64(self.seed ^ splitmix64(i as u64 ^ splitmix64(j as u64 ^ splitmix64(k as u64))));
+        let offset = Vec3::new(unit(hash), unit(splitmix64(hash)), unit(splitmix64(hash ^ 1)));
+        (Point3::new(i as f64, j as f64, k as f64) + offset, hash)
+    }
+}
+
+impl Texture for Worley {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let p = *p * self.scale;
+        let (i, j, k) = (p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64);
+        let (mut f1, mut f2, mut nearest) = (f64::INFINITY, f64::INFINITY, 0);
+        // Search rings of cells outward from p's own. Every cell in ring n is at
+        // least n - 1 away, so the search stops once that passes F2; cells whose
+        // nearest corner is already further than F2 are skipped.
+        let gap = |x: f64, cell: i64| (cell as f64 - x).max(x - cell as f64 - 1.0).max(0.0);
+        let mut ring: i64 = 0;
+        while ((ring - 1) as f64) < f2 {
+            for di in -ring..=ring {
+                for dj in -ring..=ring {
+                    for dk in -ring..=ring {
+                        if di.abs().max(dj.abs()).max(dk.abs()) != ring {
+                            continue;
+                        }
+                        let (gx, gy, gz) = (gap(p.x, i + di), gap(p.y, j + dj), gap(p.z, k + dk));
+                        if gx * gx + gy * gy + gz * gz >= f2 * f2 {
+                            continue;
+                        }
+                        let (feature, hash) = self.feature(i + di, j + dj, k + dk);
+                        let distance = (feature - p).length();
+                        if distance < f1 {
+                            (f2, f1, nearest) = (f1, distance, hash);
+                        } else if distance < f2 {
+                            f2 = distance;
+                        }
+                    }
+                }
+            }
+            ring += 1;
+        }
+        let grey = |x: f64| {
+            let x = x.clamp(0.0, 1.0);
+            Color::new(x, x, x)
+        };
+        match self.mode {
+            CellularMode::Distance => grey(f1),
+            CellularMode::Edges => grey(f2 - f1),
+            CellularMode::Cells => {
+                let h = splitmix64(nearest ^ 2);
+                Color::new(unit(h), unit(splitmix64(h)), unit(splitmix64(h ^ 3)))
+            }
+        }
+    }
+}
+
+// Courses of bricks over the unit UV square, `columns` bricks across and `rows`
+// high, separated by mortar lines `mortar_width` of a brick wide. Each row is
+// shifted by half a brick unless told otherwise.
+pub struct Brick {
+    columns: f64,
+    rows: f64,
+    mortar_width: f64,
+    row_offset: f64,
+    brick: Rc<dyn Texture>,
+    mortar: Rc<dyn Texture>,
+}
+
+impl Brick {
+    pub fn new(columns: f64, rows: f64, mortar_width: f64, brick: Rc<dyn Texture>, mortar: Rc<dyn Texture>) -> Brick {
+        Brick { columns, rows, mortar_width, row_offset: 0.5, brick, mortar }
+    }
+
+    pub fn from_colors(columns: f64, rows: f64, mortar_width: f64, brick: Color, mortar: Color) -> Brick {
+        Brick::new(columns, rows, mortar_width, Rc::new(SolidColor::new(brick)), Rc::new(SolidColor::new(mortar)))
+    }
+
+    // Shift of each row against the one below, in bricks; 0 lines the joints up
+    // into square tiles.
+    pub fn with_row_offset(mut self, row_offset: f64) -> Brick {
+        self.row_offset = row_offset;
+        self
+    }
+}
+
+impl Texture for Brick {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let y = v * self.rows;
+        let row = y.floor();
+        let x = u * self.columns + row * self.row_offset;
+        // Half the mortar sits on each side of the joint.
+        let half = 0.5 * self.mortar_width;
+        let in_mortar = |f: f64| {
+            let f = f - f.floor();
+            f < half || f > 1.0 - half
+        };
+        if in_mortar(x) || in_mortar(y) {
+            self.mortar.value(u, v, p)
+        } else {
+            self.brick.value(u, v, p)
+        }
+    }
+}
+
+// Blend from `from` at `start` to `to` at `end`, constant across planes
+// perpendicular to the line between them and clamped beyond its ends. The two
+// ends must differ, since they set the gradient's direction.
+pub struct LinearGradient {
+    start: Point3,
+    direction: Vec3,
+    from: Rc<dyn Texture>,
+    to: Rc<dyn Texture>,
+}
+
+impl LinearGradient {
+    pub fn new(start: Point3, end: Point3, from: Rc<dyn Texture>, to: Rc<dyn Texture>) -> LinearGradient {
+        assert!((end - start).length_squared() > 0.0, "LinearGradient needs distinct start and end points.");
+        // Scaled so the dot product with `p - start` is 1 at `end`.
+        let direction = (end - start) / (end - start).length_squared();
+        LinearGradient { start, direction, from, to }
+    }
+
+    pub fn from_colors(start: Point3, end: Point3, from: Color, to: Color) -> LinearGradient {
+        LinearGradient::new(start, end, Rc::new(SolidColor::new(from)), Rc::new(SolidColor::new(to)))
+    }
+}
+
+impl Texture for LinearGradient {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let t = vec::dot(&(*p - self.start), &self.direction).clamp(0.0, 1.0);
+        lerp(self.from.value(u, v, p), self.to.value(u, v, p), t)
+    }
+}
+
+// Blend from `inner` at `center` to `outer` at `radius` away from it, and
+// `outer` everywhere further out. A radius of 0 gives `outer` everywhere.
+pub struct RadialGradient {
+    center: Point3,
+    radius: f64,
+    inner: Rc<dyn Texture>,
+    outer: Rc<dyn Texture>,
+}
+
+impl RadialGradient {
+    pub fn new(center: Point3, radius: f64, inner: Rc<dyn Texture>, outer: Rc<dyn Texture>) -> RadialGradient {
+        RadialGradient { center, radius, inner, outer }
+    }
+
+    pub fn from_colors(center: Point3, radius: f64, inner: Color, outer: Color) -> RadialGradient {
+        RadialGradient::new(center, radius, Rc::new(SolidColor::new(inner)), Rc::new(SolidColor::new(outer)))
+    }
+}
+
+impl Texture for RadialGradient {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let distance = (*p - self.center).length();
+        let t = if distance < self.radius { distance / self.radius } else { 1.0 };
+        lerp(self.inner.value(u, v, p), self.outer.value(u, v, p), t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::procedural::{Brick, CellularMode, LinearGradient, RadialGradient, Worley};
+    use crate::texture::{Remap, Texture};
+    use crate::vec::Point3;
+
+    #[test]
+    fn check_worley_is_seeded_and_continuous() {
+        let p = Point3::new(0.3, 1.7, -4.2);
+        let a = Worley::new(2.0, CellularMode::Distance, 5);
+        assert_eq!(a.value(0.0, 0.0, &p).x, Worley::new(2.0, CellularMode::Distance, 5).value(0.0, 0.0, &p).x);
+        assert_ne!(a.value(0.0, 0.0, &p).x, Worley::new(2.0, CellularMode::Distance, 6).value(0.0, 0.0, &p).x);
+
+        // F1 changes no faster than the scaled distance travelled.
+        let step = 0.01;
+        let mut previous = a.value(0.0, 0.0, &Point3::new(0.0, 0.5, 0.5)).x;
+        for i in 1..1000 {
+            let value = a.value(0.0, 0.0, &Point3::new(i as f64 * step, 0.5, 0.5)).x;
+            assert!((0.0..=1.0).contains(&value));
+            assert!((value - previous).abs() <= 2.0 * step + 1e-9);
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn check_worley_cells_change_on_edges() {
+        let cells = Worley::new(1.0, CellularMode::Cells, 9);
+        let edges = Worley::new(1.0, CellularMode::Edges, 9);
+        let step = 0.01;
+        let mut changes = 0;
+        let mut previous = cells.value(0.0, 0.0, &Point3::new(0.0, 0.2, 0.7));
+        for i in 1..1000 {
+            let p = Point3::new(i as f64 * step, 0.2, 0.7);
+            let color = cells.value(0.0, 0.0, &p);
+            if format!("{}", color) != format!("{}", previous) {
+                // A new cell only starts where the two nearest points are level.
+                changes += 1;
+                assert!(edges.value(0.0, 0.0, &p).x < 2.0 * step);
+            }
+            previous = color;
+        }
+        assert!(changes > 3);
+    }
+
+    #[test]
+    fn check_worley_matches_exhaustive_search() {
+        let edges = Worley::new(1.0, CellularMode::Edges, 4);
+        let mut points: Vec<Point3> = (0..500)
+            .map(|n| Point3::new(n as f64 * 0.137, n as f64 * 0.291 - 40.0, (n * n % 97) as f64 * 0.053))
+            .collect();
+        // Here the second nearest feature point is two cells away.
+        points.push(Point3::new(8792.386, 18635.798, 0.0));
+        for p in points {
+            let mut distances = Vec::new();
+            for di in -3..=3 {
+                for dj in -3..=3 {
+                    for dk in -3..=3 {
+                        let cell = (p.x.floor() as i64 + di, p.y.floor() as i64 + dj, p.z.floor() as i64 + dk);
+                        distances.push((edges.feature(cell.0, cell.1, cell.2).0 - p).length());
+                    }
+                }
+            }
+            distances.sort_by(f64::total_cmp);
+            assert_eq!((distances[1] - distances[0]).clamp(0.0, 1.0), edges.value(0.0, 0.0, &p).x);
+        }
+    }
+
+    #[test]
+    fn check_brick() {
+        let p = Point3::new(0.0, 0.0, 0.0);
+        let brick = Brick::from_colors(4.0, 4.0, 0.1, Color::new(0.6, 0.2, 0.1), Color::new(0.8, 0.8, 0.8));
+        assert_eq!(format!("{}", brick.value(0.125, 0.125, &p)), "0.6 0.2 0.1");
+        // Horizontal joint between the first two rows.
+        assert_eq!(format!("{}", brick.value(0.125, 0.25, &p)), "0.8 0.8 0.8");
+        // The second row is shifted by half a brick, so u = 0.25 is mid-brick there
+        // but a joint in the first row.
+        assert_eq!(format!("{}", brick.value(0.25, 0.125, &p)), "0.8 0.8 0.8");
+        assert_eq!(format!("{}", brick.value(0.25, 0.375, &p)), "0.6 0.2 0.1");
+
+        let tiles = Brick::from_colors(4.0, 4.0, 0.1, Color::new(0.6, 0.2, 0.1), Color::new(0.8, 0.8, 0.8))
+            .with_row_offset(0.0);
+        assert_eq!(format!("{}", tiles.value(0.25, 0.375, &p)), "0.8 0.8 0.8");
+    }
+
+    #[test]
+    fn check_gradients() {
+        let black = Color::new(0.0, 0.0, 0.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let linear = LinearGradient::from_colors(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 4.0, 0.0), black, white);
+        assert_approx_eq!(0.25, linear.value(0.0, 0.0, &Point3::new(3.0, 1.0, -2.0)).x, 1e-12);
+        assert_eq!(format!("{}", linear.value(0.0, 0.0, &Point3::new(0.0, -1.0, 0.0))), "0 0 0");
+        assert_eq!(format!("{}", linear.value(0.0, 0.0, &Point3::new(0.0, 9.0, 0.0))), "1 1 1");
+
+        let radial = RadialGradient::from_colors(Point3::new(1.0, 1.0, 1.0), 2.0, white, black);
+        assert_approx_eq!(0.5, radial.value(0.0, 0.0, &Point3::new(1.0, 2.0, 1.0)).y, 1e-12);
+        assert_eq!(format!("{}", radial.value(0.0, 0.0, &Point3::new(5.0, 1.0, 1.0))), "0 0 0");
+        let point = RadialGradient::from_colors(Point3::new(1.0, 1.0, 1.0), 0.0, white, black);
+        assert_eq!(format!("{}", point.value(0.0, 0.0, &Point3::new(1.0, 1.0, 1.0))), "0 0 0");
+
+        // Remapping the gradient's middle half stretches it into a harder ramp.
+        let ramp = Remap::new(Rc::new(radial), 0.25, 0.75, black, white);
+        assert_approx_eq!(0.5, ramp.value(0.0, 0.0, &Point3::new(1.0, 2.0, 1.0)).z, 1e-12);
+        assert_eq!(format!("{}", ramp.value(0.0, 0.0, &Point3::new(1.0, 1.2, 1.0))), "1 1 1");
+    }
+
+    #[test]
+    #[should_panic(expected = "LinearGradient needs distinct start and end points.")]
+    fn check_linear_gradient_needs_a_direction() {
+        let p = Point3::new(1.0, 2.0, 3.0);
+        LinearGradient::from_colors(p, p, Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0));
+    }
+}
@@ -0,0 +1,118 @@
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::Aabb;
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, random_double, Ray};
+use crate::vec::Point3;
+
+// Parallelogram spanned by the edges `u` and `v` from the corner `q`. The
+// outward normal follows the right-hand rule, u × v.
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    d: f64,
+    w: Vec3,
+    area: f64,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat_ptr: Rc<dyn Scatter>) -> Quad {
+        let n = vec::cross(&u, &v);
+        let normal = vec::unit_vector(n);
+        let d = vec::dot(&normal, &q);
+        let w = n / vec::dot(&n, &n);
+        let area = n.length();
+        Quad { q, u, v, normal, d, w, area, mat_ptr }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    // Uniformly distributed point on the surface, for sampling the quad as an area light.
+    pub fn random_point(&self) -> Point3 {
+        self.q + random_double() * self.u + random_double() * self.v
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denom = vec::dot(&self.normal, &r.direction());
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = (self.d - vec::dot(&self.normal, &r.origin())) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        // Express the hit point in the (u, v) frame of the plane to get the
+        // planar coordinates, which double as texture coordinates.
+        let p = r.at(t);
+        let planar_hit = p - self.q;
+        let alpha = vec::dot(&self.w, &vec::cross(&planar_hit, &self.v));
+        let beta = vec::dot(&self.w, &vec::cross(&self.u, &planar_hit));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let mut rec = HitRecord::new(p, self.normal, Rc::clone(&self.mat_ptr), t);
+        rec.u = alpha;
+        rec.v = beta;
+        rec.set_face_normal(r);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        let corners = [self.q, self.q + self.u, self.q + self.v, self.q + self.u + self.v];
+        Some(Aabb::from_points(&corners).pad(0.0001))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::quad::Quad;
+    use crate::raytracing::{Hittable, Ray};
+    use crate::vec::{Point3, Vec3};
+
+    fn slanted() -> Quad {
+        Quad::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0),
+                  Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_hit_planar_coordinates() {
+        let r = Ray::new(Point3::new(1.5, 0.5, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = slanted().hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(3.0, rec.t, 1e-9);
+        assert_approx_eq!(0.5, rec.u, 1e-9);
+        assert_approx_eq!(0.5, rec.v, 1e-9);
+        assert!(rec.front_face);
+        assert_approx_eq!(2.0, slanted().area(), 1e-9);
+    }
+
+    #[test]
+    fn check_miss_outside_parallelogram() {
+        let r = Ray::new(Point3::new(0.2, 0.8, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(slanted().hit(&r, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_bounding_box() {
+        let bbox = slanted().bounding_box(0.0, 1.0).unwrap();
+        assert_approx_eq!(0.0, bbox.min().x, 1e-9);
+        assert_approx_eq!(3.0, bbox.max().x, 1e-9);
+        assert_approx_eq!(1.0, bbox.max().y, 1e-9);
+        assert!(bbox.max().z > bbox.min().z);
+    }
+}
@@ -4,6 +4,7 @@ use std::rc::Rc;
 use rand::Rng;
 
 use crate::{vec, Vec3};
+use crate::aabb::{Aabb, surrounding_box};
 use crate::color::Color;
 use crate::material::Scatter;
 use crate::vec::Point3;
@@ -13,6 +14,8 @@ pub struct HitRecord {
     pub normal: Vec3,
     pub mat_ptr: Rc<dyn Scatter>,
     pub t: f64,
+    pub u: f64,
+    pub v: f64,
     pub front_face: bool,
 }
 
@@ -27,14 +30,16 @@ impl HitRecord {
         };
     }
     pub fn new(p: Point3, normal: Vec3, mat_ptr: Rc<dyn Scatter>, t: f64) -> HitRecord {
-        HitRecord { p, normal, mat_ptr, t, front_face: false }
+        HitRecord { p, normal, mat_ptr, t, u: 0.0, v: 0.0, front_face: false }
     }
 }
 
 pub trait Hittable {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb>;
 }
 
+#[derive(Default)]
 pub struct HittableList {
     objects: Vec<Rc<dyn Hittable>>,
 }
@@ -56,6 +61,9 @@ impl HittableList {
     pub fn add(&mut self, object: Rc<dyn Hittable>) {
         self.objects.push(object);
     }
+    pub fn objects(&self) -> &[Rc<dyn Hittable>] {
+        &self.objects
+    }
 }
 
 impl Hittable for HittableList {
@@ -70,6 +78,18 @@ impl Hittable for HittableList {
         }
         temp_rec
     }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let mut output_box: Option<Aabb> = None;
+        for object in &self.objects {
+            let temp_box = object.bounding_box(time0, time1)?;
+            output_box = match output_box {
+                Some(output_box) => Some(surrounding_box(&output_box, &temp_box)),
+                None => Some(temp_box),
+            };
+        }
+        output_box
+    }
 }
 
 pub struct Sphere {
@@ -86,43 +106,66 @@ impl Sphere {
 
 impl Hittable for Sphere {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let oc: Vec3 = r.origin() - self.center;
-        let a = r.direction().length_squared();
-        let half_b = vec::dot(&oc, &r.direction());
-        let c = oc.length_squared() - self.radius * self.radius;
-        let discriminant = half_b * half_b - a * c;
-        if discriminant < 0.0 {
-            return None;
-        }
-        let sqrtd = discriminant.sqrt();
-        let mut root = (-half_b - sqrtd) / a;
+        hit_sphere(self.center, self.radius, &self.mat_ptr, r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        let radius = Vec3::new(self.radius.abs(), self.radius.abs(), self.radius.abs());
+        Some(Aabb::new(self.center - radius, self.center + radius))
+    }
+}
+
+pub fn hit_sphere(center: Point3, radius: f64, mat_ptr: &Rc<dyn Scatter>, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    let oc: Vec3 = r.origin() - center;
+    let a = r.direction().length_squared();
+    let half_b = vec::dot(&oc, &r.direction());
+    let c = oc.length_squared() - radius * radius;
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let sqrtd = discriminant.sqrt();
+    let mut root = (-half_b - sqrtd) / a;
+    if root < t_min || root > t_max {
+        root = (-half_b + sqrtd) / a;
         if root < t_min || root > t_max {
-            root = (-half_b + sqrtd) / a;
-            if root < t_min || root > t_max {
-                return None;
-            }
+            return None;
         }
+    }
 
-        let t = root;
-        let p = r.at(t);
-        let outward_normal = (p - self.center) / self.radius;
-        let mat_ptr = Rc::clone(&self.mat_ptr);
-        let mut rec = HitRecord::new(p, outward_normal, mat_ptr, t);
-        rec.set_face_normal(r);
+    let t = root;
+    let p = r.at(t);
+    let outward_normal = (p - center) / radius;
+    let mat_ptr = Rc::clone(mat_ptr);
+    let mut rec = HitRecord::new(p, outward_normal, mat_ptr, t);
+    rec.set_face_normal(r);
+    (rec.u, rec.v) = sphere_uv(vec::unit_vector(p - center));
 
-        Some(rec)
-    }
+    Some(rec)
 }
 
+// Longitude/latitude coordinates of a point on the unit sphere: u runs around the
+// y axis starting from -x, v from the south pole (0) to the north pole (1).
+pub fn sphere_uv(p: Point3) -> (f64, f64) {
+    let theta = (-p.y).clamp(-1.0, 1.0).acos();
+    let phi = f64::atan2(-p.z, p.x) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
 
 pub struct Ray {
     orig: Vec3,
     dir: Vec3,
+    time: f64,
 }
 
 impl Ray {
     pub fn new(a: Vec3, b: Vec3) -> Ray {
-        Ray { orig: a, dir: b }
+        Ray::with_time(a, b, 0.0)
+    }
+
+    // Ray sent at `time` within the camera's shutter interval.
+    pub fn with_time(a: Vec3, b: Vec3, time: f64) -> Ray {
+        Ray { orig: a, dir: b, time }
     }
 
     pub fn origin(&self) -> Vec3 {
@@ -133,25 +176,50 @@ impl Ray {
         self.dir
     }
 
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
     pub fn at(&self, t: f64) -> Vec3 {
         self.orig + (self.dir * t)
     }
 }
 
 
-pub fn ray_color(ray: &Ray, world: &dyn Hittable, depth: usize) -> Color {
+// What a ray sees when it escapes the scene.
+#[derive(Copy, Clone, Debug)]
+pub enum Background {
+    // White to blue gradient along y, as if lit by the sky.
+    Sky,
+    // Uniform color; black for scenes lit only by their own lights.
+    Solid(Color),
+}
+
+impl Background {
+    pub fn color(&self, ray: &Ray) -> Color {
+        match self {
+            Background::Sky => {
+                let unit_direction = vec::unit_vector(ray.direction());
+                let t = 0.5 * (unit_direction.y + 1.0);
+                (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
+            }
+            Background::Solid(color) => *color,
+        }
+    }
+}
+
+pub fn ray_color(ray: &Ray, background: &Background, world: &dyn Hittable, depth: usize) -> Color {
     if depth == 0 {
         return Color::new(0.0, 0.0, 0.0);
     }
     if let Some(hit_record) = world.hit(ray, 0.001, f64::INFINITY) {
+        let emitted = hit_record.mat_ptr.emitted(hit_record.u, hit_record.v, &hit_record.p);
         if let Some((scattered, attenuation)) = hit_record.mat_ptr.scatter(ray, &hit_record) {
-            return attenuation * ray_color(&scattered, world, depth - 1);
+            return emitted + attenuation * ray_color(&scattered, background, world, depth - 1);
         }
-        return Color::new(0.0, 0.0, 0.0);
+        return emitted;
     }
-    let unit_direction = vec::unit_vector(ray.direction());
-    let t = 0.5 * (unit_direction.y + 1.0);
-    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
+    background.color(ray)
 }
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
@@ -169,9 +237,11 @@ pub fn random_double_range(min: f64, max: f64) -> f64 {
 
 #[cfg(test)]
 mod tests {
-    use assert_approx_eq::assert_approx_eq;
+    use std::rc::Rc;
 
-    use crate::raytracing::Ray;
+    use crate::color::Color;
+    use crate::material::{DiffuseLight, Lambertian};
+    use crate::raytracing::{Background, Hittable, HittableList, Ray, ray_color, Sphere, sphere_uv};
     use crate::Vec3;
     use crate::vec::Point3;
 
@@ -180,6 +250,41 @@ mod tests {
         let a = Vec3::new(1.0, 2.5, 3.8);
         let b = Vec3::new(2.5, 2.0, 3.0);
         let ray = Ray::new(a, b);
-        assert_eq!(format!("{}", ray.at(2 as f64)), "6 6.5 9.8");
+        assert_eq!(format!("{}", ray.at(2.0)), "6 6.5 9.8");
+    }
+
+    #[test]
+    fn check_bounding_box() {
+        let material = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut world = HittableList::new();
+        world.add(Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, material.clone())));
+        world.add(Rc::new(Sphere::new(Point3::new(3.0, 1.0, 0.0), -0.5, material)));
+        let bbox = world.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(format!("{}", bbox.min()), "-1 -1 -1");
+        assert_eq!(format!("{}", bbox.max()), "3.5 1.5 1");
+        assert!(HittableList::new().bounding_box(0.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn check_ray_color_emission() {
+        let mut world = HittableList::new();
+        world.add(Rc::new(Sphere::new(Point3::new(0.0, 0.0, -2.0), 0.5, Rc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0))))));
+        let black = Background::Solid(Color::new(0.0, 0.0, 0.0));
+        let at_lamp = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(format!("{}", ray_color(&at_lamp, &black, &world, 10)), "4 4 4");
+        let away = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(format!("{}", ray_color(&away, &black, &world, 10)), "0 0 0");
+        assert_eq!(format!("{}", ray_color(&away, &Background::Sky, &world, 10)), "0.75 0.85 1");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn check_sphere_uv() {
+        assert_eq!((0.5, 0.5), sphere_uv(Point3::new(1.0, 0.0, 0.0)));
+        assert_eq!((0.25, 0.5), sphere_uv(Point3::new(0.0, 0.0, 1.0)));
+        assert_eq!(1.0, sphere_uv(Point3::new(0.0, 1.0, 0.0)).1);
+
+        let sphere = Sphere::new(Point3::new(0.0, 0.0, 0.0), 2.0, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let rec = sphere.hit(&Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)), 0.0, f64::INFINITY).unwrap();
+        assert_eq!((0.25, 0.5), (rec.u, rec.v));
+    }
+}
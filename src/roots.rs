@@ -0,0 +1,187 @@
+// Real roots of low-degree polynomials, used by the analytic primitives.
+
+// Real roots of a x² + b x + c in ascending order; degenerates to the linear
+// equation when `a` vanishes.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return None;
+        }
+        let x = -c / b;
+        return Some((x, x));
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let sqrtd = discriminant.sqrt();
+    // Avoids the cancellation of -b ± sqrtd when the two are close.
+    let q = -0.5 * (b + b.signum() * sqrtd);
+    let (x0, x1) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+    Some((x0.min(x1), x0.max(x1)))
+}
+
+// Real roots of a x³ + b x² + c x + d in ascending order, by the trigonometric
+// method when there are three of them and Cardano's formula otherwise.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return match solve_quadratic(b, c, d) {
+            Some((x0, x1)) if x0 == x1 => vec![x0],
+            Some((x0, x1)) => vec![x0, x1],
+            None => vec![],
+        };
+    }
+    let (a2, a1, a0) = (b / a, c / a, d / a);
+    let q = (a2 * a2 - 3.0 * a1) / 9.0;
+    let r = (2.0 * a2 * a2 * a2 - 9.0 * a2 * a1 + 27.0 * a0) / 54.0;
+    let q3 = q * q * q;
+    let shift = a2 / 3.0;
+
+    let mut roots = if r * r < q3 {
+        let theta = (r / q3.sqrt()).clamp(-1.0, 1.0).acos();
+        let m = -2.0 * q.sqrt();
+        vec![m * (theta / 3.0).cos() - shift,
+             m * ((theta + 2.0 * std::f64::consts::PI) / 3.0).cos() - shift,
+             m * ((theta - 2.0 * std::f64::consts::PI) / 3.0).cos() - shift]
+    } else {
+        let s = -r.signum() * (r.abs() + (r * r - q3).sqrt()).cbrt();
+        let t = if s == 0.0 { 0.0 } else { q / s };
+        vec![s + t - shift]
+    };
+    for x in &mut roots {
+        *x = polish(&[a, b, c, d], *x);
+    }
+    roots.sort_by(|x, y| x.total_cmp(y));
+    roots
+}
+
+// Real roots of a x⁴ + b x³ + c x² + d x + e in ascending order. Uses Ferrari's
+// method on the depressed quartic, then refines every root with a few Newton
+// steps on the original polynomial to recover the precision lost on the way.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return solve_cubic(b, c, d, e);
+    }
+    let (a3, a2, a1, a0) = (b / a, c / a, d / a, e / a);
+
+    // Substituting x = y - a3 / 4 gives y⁴ + p y² + q y + r = 0.
+    let shift = a3 / 4.0;
+    let a3_2 = a3 * a3;
+    let p = a2 - 3.0 * a3_2 / 8.0;
+    let q = a1 - a3 * a2 / 2.0 + a3_2 * a3 / 8.0;
+    let r = a0 - a3 * a1 / 4.0 + a3_2 * a2 / 16.0 - 3.0 * a3_2 * a3_2 / 256.0;
+
+    let mut ys = Vec::with_capacity(4);
+    if q.abs() < 1e-12 {
+        // Biquadratic: a quadratic in y².
+        if let Some((z0, z1)) = solve_quadratic(1.0, p, r) {
+            for z in [z0, z1] {
+                if z >= 0.0 {
+                    ys.push(z.sqrt());
+                    ys.push(-z.sqrt());
+                }
+            }
+        }
+    } else {
+        // Any positive root m of the resolvent cubic splits the quartic into
+        // (y² + s y + p/2 + m - q/(2s)) (y² - s y + p/2 + m + q/(2s)) with s = √(2m).
+        let m = solve_cubic(1.0, p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m > 0.0 {
+            let s = (2.0 * m).sqrt();
+            for (sign, offset) in [(1.0, -q / (2.0 * s)), (-1.0, q / (2.0 * s))] {
+                if let Some((y0, y1)) = solve_quadratic(1.0, sign * s, p / 2.0 + m + offset) {
+                    ys.push(y0);
+                    ys.push(y1);
+                }
+            }
+        }
+    }
+
+    let coefficients = [a, b, c, d, e];
+    let mut roots: Vec<f64> = ys.into_iter().map(|y| polish(&coefficients, y - shift)).collect();
+    roots.sort_by(|x, y| x.total_cmp(y));
+    roots.dedup_by(|x, y| (*x - *y).abs() < 1e-9);
+    roots
+}
+
+// Newton iterations on the polynomial with `coefficients` (highest degree first).
+fn polish(coefficients: &[f64], x: f64) -> f64 {
+    let mut x = x;
+    for _ in 0..4 {
+        let (mut value, mut derivative) = (0.0, 0.0);
+        for &k in coefficients {
+            derivative = derivative * x + value;
+            value = value * x + k;
+        }
+        if derivative.abs() < 1e-15 {
+            break;
+        }
+        let next = x - value / derivative;
+        if !next.is_finite() {
+            break;
+        }
+        x = next;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::roots::{solve_cubic, solve_quadratic, solve_quartic};
+
+    #[test]
+    fn check_quadratic() {
+        let (x0, x1) = solve_quadratic(2.0, -6.0, 4.0).unwrap();
+        assert_approx_eq!(1.0, x0, 1e-12);
+        assert_approx_eq!(2.0, x1, 1e-12);
+        assert!(solve_quadratic(1.0, 0.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn check_cubic() {
+        // (x + 2)(x - 1)(x - 3)
+        let roots = solve_cubic(1.0, -2.0, -5.0, 6.0);
+        assert_eq!(roots.len(), 3);
+        for (expected, actual) in [-2.0, 1.0, 3.0].iter().zip(&roots) {
+            assert_approx_eq!(expected, actual, 1e-9);
+        }
+        // x³ + x + 1 has a single real root.
+        let roots = solve_cubic(1.0, 0.0, 1.0, 1.0);
+        assert_eq!(roots.len(), 1);
+        assert_approx_eq!(-0.6823278038280193, roots[0], 1e-9);
+    }
+
+    #[test]
+    fn check_quartic_four_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        let roots = solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0);
+        assert_eq!(roots.len(), 4);
+        for (expected, actual) in [1.0, 2.0, 3.0, 4.0].iter().zip(&roots) {
+            assert_approx_eq!(expected, actual, 1e-9);
+        }
+    }
+
+    #[test]
+    fn check_quartic_biquadratic_and_no_roots() {
+        // (x² - 1)(x² - 4)
+        let roots = solve_quartic(2.0, 0.0, -10.0, 0.0, 8.0);
+        assert_eq!(roots.len(), 4);
+        assert_approx_eq!(-2.0, roots[0], 1e-9);
+        assert_approx_eq!(1.0, roots[2], 1e-9);
+        // (x² + 1)(x² + 2)
+        assert!(solve_quartic(1.0, 0.0, 3.0, 0.0, 2.0).is_empty());
+    }
+
+    #[test]
+    fn check_quartic_wide_range() {
+        // (x - 0.001)(x - 1000)(x² + 1)
+        let roots = solve_quartic(1.0, -1000.001, 2.0, -1000.001, 1.0);
+        assert_eq!(roots.len(), 2);
+        assert_approx_eq!(0.001, roots[0], 1e-12);
+        assert_approx_eq!(1000.0, roots[1], 1e-9);
+    }
+}
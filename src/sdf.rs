@@ -0,0 +1,371 @@
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::{Aabb, surrounding_box};
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, Ray, sphere_uv};
+use crate::vec::Point3;
+
+// Sphere tracing gives up after this many steps, e.g. on rays grazing a surface.
+const MAX_STEPS: usize = 256;
+// A point this close to the surface counts as a hit.
+const HIT_DISTANCE: f64 = 1e-6;
+// How far rays are marched through an unbounded field before giving up.
+const MAX_DISTANCE: f64 = 1000.0;
+// Step for the finite-difference gradient.
+const GRADIENT_STEP: f64 = 1e-6;
+
+// Signed distance to a surface: negative inside, positive outside. The value may
+// underestimate the true distance but must never overshoot it, or sphere tracing
+// steps through the surface.
+pub trait Sdf {
+    fn distance(&self, p: Point3) -> f64;
+    // Box around the inside of the field, or None if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
+}
+
+pub struct SdfSphere {
+    center: Point3,
+    radius: f64,
+}
+
+impl SdfSphere {
+    pub fn new(center: Point3, radius: f64) -> SdfSphere {
+        SdfSphere { center, radius }
+    }
+}
+
+impl Sdf for SdfSphere {
+    fn distance(&self, p: Point3) -> f64 {
+        (p - self.center).length() - self.radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - radius, self.center + radius))
+    }
+}
+
+// Exact distance to an origin-centered box with the given half extents.
+fn box_distance(p: Vec3, half_extents: Vec3) -> f64 {
+    let q = Vec3::new(p.x.abs() - half_extents.x, p.y.abs() - half_extents.y, p.z.abs() - half_extents.z);
+    let outside = Vec3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).length();
+    let inside = q.x.max(q.y).max(q.z).min(0.0);
+    outside + inside
+}
+
+pub struct SdfBox {
+    center: Point3,
+    half_extents: Vec3,
+}
+
+impl SdfBox {
+    pub fn new(center: Point3, half_extents: Vec3) -> SdfBox {
+        SdfBox { center, half_extents }
+    }
+}
+
+impl Sdf for SdfBox {
+    fn distance(&self, p: Point3) -> f64 {
+        box_distance(p - self.center, self.half_extents)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.center - self.half_extents, self.center + self.half_extents))
+    }
+}
+
+// Box whose edges and corners are rounded off with `radius`, within the same
+// half extents as the sharp box.
+pub struct RoundBox {
+    center: Point3,
+    half_extents: Vec3,
+    radius: f64,
+}
+
+impl RoundBox {
+    pub fn new(center: Point3, half_extents: Vec3, radius: f64) -> RoundBox {
+        RoundBox { center, half_extents, radius }
+    }
+}
+
+impl Sdf for RoundBox {
+    fn distance(&self, p: Point3) -> f64 {
+        let core = self.half_extents - Vec3::new(self.radius, self.radius, self.radius);
+        box_distance(p - self.center, core) - self.radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.center - self.half_extents, self.center + self.half_extents))
+    }
+}
+
+// Ring in the xz plane around `center`, like `Torus` with a y axis.
+pub struct SdfTorus {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl SdfTorus {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64) -> SdfTorus {
+        SdfTorus { center, major_radius, minor_radius }
+    }
+}
+
+impl Sdf for SdfTorus {
+    fn distance(&self, p: Point3) -> f64 {
+        let p = p - self.center;
+        let ring = (p.x * p.x + p.z * p.z).sqrt() - self.major_radius;
+        (ring * ring + p.y * p.y).sqrt() - self.minor_radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = self.major_radius + self.minor_radius;
+        let half = Vec3::new(extent, self.minor_radius, extent);
+        Some(Aabb::new(self.center - half, self.center + half))
+    }
+}
+
+// Union of two fields with the seam rounded over a width of `smoothness`; a
+// smoothness of 0 or less gives the plain, sharp union.
+pub struct SmoothUnion {
+    a: Rc<dyn Sdf>,
+    b: Rc<dyn Sdf>,
+    smoothness: f64,
+}
+
+impl SmoothUnion {
+    pub fn new(a: Rc<dyn Sdf>, b: Rc<dyn Sdf>, smoothness: f64) -> SmoothUnion {
+        SmoothUnion { a, b, smoothness }
+    }
+}
+
+impl Sdf for SmoothUnion {
+    // Polynomial smooth minimum.
+    fn distance(&self, p: Point3) -> f64 {
+        let (d1, d2, k) = (self.a.distance(p), self.b.distance(p), self.smoothness);
+        if k <= 0.0 {
+            return d1.min(d2);
+        }
+        let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0.0, 1.0);
+        d2 + (d1 - d2) * h - k * h * (1.0 - h)
+    }
+
+    // The blend bulges out by at most a quarter of the smoothness.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox = surrounding_box(&self.a.bounding_box()?, &self.b.bounding_box()?);
+        let k = self.smoothness.max(0.0);
+        let bulge = 0.25 * Vec3::new(k, k, k);
+        Some(Aabb::new(bbox.min() - bulge, bbox.max() + bulge))
+    }
+}
+
+// `base` with `cut` carved out of it, rounding the new edges over `smoothness`;
+// a smoothness of 0 or less leaves them sharp.
+pub struct SmoothSubtraction {
+    base: Rc<dyn Sdf>,
+    cut: Rc<dyn Sdf>,
+    smoothness: f64,
+}
+
+impl SmoothSubtraction {
+    pub fn new(base: Rc<dyn Sdf>, cut: Rc<dyn Sdf>, smoothness: f64) -> SmoothSubtraction {
+        SmoothSubtraction { base, cut, smoothness }
+    }
+}
+
+impl Sdf for SmoothSubtraction {
+    fn distance(&self, p: Point3) -> f64 {
+        let (d1, d2, k) = (self.cut.distance(p), self.base.distance(p), self.smoothness);
+        if k <= 0.0 {
+            return d2.max(-d1);
+        }
+        let h = (0.5 - 0.5 * (d2 + d1) / k).clamp(0.0, 1.0);
+        d2 + (-d1 - d2) * h + k * h * (1.0 - h)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.base.bounding_box()
+    }
+}
+
+// Infinite copies of a field, one per cell of size `period` centered on the
+// origin. A zero component leaves that axis unrepeated. The shape has to fit
+// inside its cell or the distances are wrong.
+pub struct Repeat {
+    shape: Rc<dyn Sdf>,
+    period: Vec3,
+}
+
+impl Repeat {
+    pub fn new(shape: Rc<dyn Sdf>, period: Vec3) -> Repeat {
+        Repeat { shape, period }
+    }
+}
+
+impl Sdf for Repeat {
+    fn distance(&self, p: Point3) -> f64 {
+        let wrap = |x: f64, period: f64| if period == 0.0 { x } else { x - period * (x / period).round() };
+        let q = Point3::new(wrap(p.x, self.period.x), wrap(p.y, self.period.y), wrap(p.z, self.period.z));
+        self.shape.distance(q)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+// Renders the zero level set of a field by sphere tracing: step along the ray
+// by the distance to the surface, which can never jump past it.
+pub struct SdfObject {
+    sdf: Rc<dyn Sdf>,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl SdfObject {
+    pub fn new(sdf: Rc<dyn Sdf>, mat_ptr: Rc<dyn Scatter>) -> SdfObject {
+        SdfObject { sdf, mat_ptr }
+    }
+
+    // Central differences on the corners of a tetrahedron; four evaluations instead of six.
+    fn normal(&self, p: Point3) -> Vec3 {
+        let h = GRADIENT_STEP;
+        let corners = [Vec3::new(1.0, -1.0, -1.0), Vec3::new(-1.0, -1.0, 1.0),
+                       Vec3::new(-1.0, 1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)];
+        let gradient = corners.iter()
+            .fold(Vec3::default(), |acc, &k| acc + k * self.sdf.distance(p + h * k));
+        vec::unit_vector(gradient)
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let length = r.direction().length();
+        let (mut t, t_end) = match self.sdf.bounding_box() {
+            Some(bbox) => bbox.clip(r, t_min, t_max)?,
+            None => (t_min, t_max.min(t_min + MAX_DISTANCE / length)),
+        };
+        // Rays starting inside march on the negated field to find the way out.
+        let side = if self.sdf.distance(r.at(t)) < 0.0 { -1.0 } else { 1.0 };
+        for _ in 0..MAX_STEPS {
+            let p = r.at(t);
+            let d = side * self.sdf.distance(p);
+            if d < HIT_DISTANCE {
+                let outward_normal = self.normal(p);
+                let mut rec = HitRecord::new(p, outward_normal, Rc::clone(&self.mat_ptr), t);
+                rec.set_face_normal(r);
+                // Implicit surfaces have no parameterization; map by normal direction.
+                (rec.u, rec.v) = sphere_uv(outward_normal);
+                return Some(rec);
+            }
+            t += d / length;
+            if t > t_end {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        self.sdf.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, Ray};
+    use crate::sdf::{Repeat, RoundBox, Sdf, SdfBox, SdfObject, SdfSphere, SdfTorus, SmoothSubtraction, SmoothUnion};
+    use crate::vec::{Point3, Vec3};
+
+    fn object(sdf: Rc<dyn Sdf>) -> SdfObject {
+        SdfObject::new(sdf, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_sphere_trace() {
+        let sphere = object(Rc::new(SdfSphere::new(Point3::new(0.0, 0.0, -5.0), 1.0)));
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -2.0));
+        let rec = sphere.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(2.0, rec.t, 1e-5);
+        assert_approx_eq!(1.0, rec.normal.z, 1e-5);
+        assert!(rec.front_face);
+        assert!(sphere.hit(&r, 0.0, 1.5).is_none());
+
+        let inside = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = sphere.hit(&inside, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(1.0, rec.t, 1e-5);
+        assert_approx_eq!(-1.0, rec.normal.x, 1e-5);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn check_box_and_round_box() {
+        let sharp = SdfBox::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 3.0));
+        assert_approx_eq!(1.0, sharp.distance(Point3::new(2.0, 0.0, 0.0)), 1e-12);
+        assert_approx_eq!(-0.5, sharp.distance(Point3::new(0.5, 0.0, 0.0)), 1e-12);
+        assert_approx_eq!(2.0_f64.sqrt(), sharp.distance(Point3::new(2.0, 3.0, 0.0)), 1e-12);
+
+        let round = RoundBox::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0), 0.5);
+        assert_approx_eq!(0.0, round.distance(Point3::new(1.0, 0.0, 0.0)), 1e-12);
+        // The corner is cut back to the sphere of radius 0.5 around (0.5, 0.5, 0.5).
+        assert_approx_eq!(0.75_f64.sqrt() - 0.5, round.distance(Point3::new(1.0, 1.0, 1.0)), 1e-12);
+
+        let r = Ray::new(Point3::new(-5.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let rec = object(Rc::new(sharp)).hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.0, rec.t, 1e-5);
+        assert_approx_eq!(-1.0, rec.normal.x, 1e-5);
+    }
+
+    #[test]
+    fn check_torus() {
+        let torus = SdfTorus::new(Point3::new(0.0, 0.0, 0.0), 2.0, 0.5);
+        assert_approx_eq!(-0.5, torus.distance(Point3::new(0.0, 0.0, 2.0)), 1e-12);
+        assert_approx_eq!(1.5, torus.distance(Point3::new(0.0, 0.0, 0.0)), 1e-12);
+        let hole = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(object(Rc::new(torus)).hit(&hole, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_smooth_operations() {
+        let a = Rc::new(SdfSphere::new(Point3::new(-1.0, 0.0, 0.0), 1.0));
+        let b = Rc::new(SdfSphere::new(Point3::new(1.0, 0.0, 0.0), 1.0));
+        // Between the spheres the blend fills in the crease.
+        let neck = Point3::new(0.0, 1.0, 0.0);
+        let sharp = a.distance(neck).min(b.distance(neck));
+        let blended = SmoothUnion::new(a.clone(), b.clone(), 0.5);
+        assert!(blended.distance(neck) < sharp);
+        // Far from the seam it matches the plain union.
+        assert_approx_eq!(1.0, blended.distance(Point3::new(-3.0, 0.0, 0.0)), 1e-12);
+
+        let bite = Rc::new(SdfSphere::new(Point3::new(0.0, 0.0, 0.0), 0.5));
+        let carved = SmoothSubtraction::new(a.clone(), bite.clone(), 0.1);
+        assert!(carved.distance(Point3::new(-0.2, 0.0, 0.0)) > 0.0);
+        assert!(carved.distance(Point3::new(-1.5, 0.0, 0.0)) < 0.0);
+
+        // Zero smoothness falls back to the sharp operations, even on the seam.
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        assert_eq!(0.0, SmoothUnion::new(a.clone(), b, 0.0).distance(origin));
+        assert_eq!(0.5, SmoothSubtraction::new(a, bite, 0.0).distance(origin));
+    }
+
+    #[test]
+    fn check_repeat() {
+        let grid = Repeat::new(Rc::new(SdfSphere::new(Point3::new(0.0, 0.0, 0.0), 0.5)), Vec3::new(4.0, 0.0, 4.0));
+        assert_approx_eq!(grid.distance(Point3::new(0.0, 0.0, 1.0)), grid.distance(Point3::new(8.0, 0.0, -3.0)), 1e-12);
+        // Along the row the first copy is the one centered at x = -4.
+        let r = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = object(Rc::new(grid)).hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(0.5, rec.t, 1e-5);
+        // Not repeated along y.
+        let above = Ray::new(Point3::new(0.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let grid = Repeat::new(Rc::new(SdfSphere::new(Point3::new(0.0, 0.0, 0.0), 0.5)), Vec3::new(4.0, 0.0, 4.0));
+        assert!(object(Rc::new(grid)).hit(&above, 0.0, f64::INFINITY).is_none());
+    }
+}
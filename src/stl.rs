@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::fs;
+use std::rc::Rc;
+
+use crate::material::Scatter;
+use crate::mesh::{MeshData, MeshError, MeshFace, TriangleMesh};
+use crate::vec::Point3;
+
+// Post-processing applied to a loaded part so it can be placed among other
+// objects without knowing the units or origin of the CAD export.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StlOptions {
+    // Move the center of the bounding box to the origin.
+    pub center: bool,
+    // Scale uniformly so the longest side of the bounding box has this length.
+    pub size: Option<f64>,
+}
+
+pub fn load_stl(path: &str, mat_ptr: Rc<dyn Scatter>, options: StlOptions) -> Result<TriangleMesh, MeshError> {
+    let bytes = fs::read(path).map_err(|source| MeshError::Io { file: path.to_string(), source })?;
+    let mut data = parse_stl(&bytes, path)?;
+    if options.center {
+        data.center();
+    }
+    if let Some(size) = options.size {
+        data.scale_to_fit(size);
+    }
+    Ok(TriangleMesh::new(data, mat_ptr))
+}
+
+// Reads binary or ASCII STL. Many binary exporters also start their header with
+// `solid`, so a file is treated as binary whenever its length matches the
+// triangle count stored in it. Stored facet normals are ignored in favour of the
+// vertex winding, and shared corners are welded into single vertices.
+pub fn parse_stl(bytes: &[u8], file: &str) -> Result<MeshData, MeshError> {
+    let data = if is_binary(bytes) {
+        parse_binary(bytes)
+    } else if bytes.starts_with(b"solid") {
+        let text = std::str::from_utf8(bytes).map_err(|_| MeshError::Format {
+            file: file.to_string(),
+            message: String::from("ascii STL is not valid UTF-8"),
+        })?;
+        parse_ascii(text, file)?
+    } else {
+        return Err(MeshError::Format {
+            file: file.to_string(),
+            message: format!("not an STL file ({} bytes, no 'solid' keyword and bad triangle count)", bytes.len()),
+        });
+    };
+    if data.faces.is_empty() {
+        return Err(MeshError::Empty { file: file.to_string() });
+    }
+    Ok(data)
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < 84 {
+        return false;
+    }
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+    // The count is untrusted; a size that does not fit in usize cannot match.
+    count.checked_mul(50).and_then(|size| size.checked_add(84)) == Some(bytes.len())
+}
+
+fn parse_binary(bytes: &[u8]) -> MeshData {
+    let mut builder = MeshBuilder::new();
+    for facet in bytes[84..].chunks_exact(50) {
+        let read = |offset: usize| {
+            f32::from_le_bytes([facet[offset], facet[offset + 1], facet[offset + 2], facet[offset + 3]]) as f64
+        };
+        // Bytes 0..12 hold the facet normal and 48..50 an attribute word.
+        let vertex = |offset: usize| Point3::new(read(offset), read(offset + 4), read(offset + 8));
+        builder.add_triangle([vertex(12), vertex(24), vertex(36)]);
+    }
+    builder.data
+}
+
+fn parse_ascii(text: &str, file: &str) -> Result<MeshData, MeshError> {
+    let mut builder = MeshBuilder::new();
+    let mut vertices: Vec<Point3> = Vec::with_capacity(3);
+    // Line of the `facet` statement that has not seen its `endfacet` yet.
+    let mut open_facet = None;
+    for (index, line) in text.lines().enumerate() {
+        let error = |message: String| MeshError::Parse { file: file.to_string(), line: index + 1, message };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["vertex", x, y, z] => {
+                let parse = |s: &str| s.parse::<f64>().map_err(|_| error(format!("invalid number '{}'", s)));
+                vertices.push(Point3::new(parse(x)?, parse(y)?, parse(z)?));
+            }
+            ["vertex", ..] => return Err(error(String::from("vertex needs 3 coordinates"))),
+            ["endloop"] => {
+                if vertices.len() != 3 {
+                    return Err(error(format!("facet has {} vertices, expected 3", vertices.len())));
+                }
+                builder.add_triangle([vertices[0], vertices[1], vertices[2]]);
+                vertices.clear();
+            }
+            ["facet", ..] => open_facet = Some(index + 1),
+            ["endfacet"] => {
+                if !vertices.is_empty() {
+                    return Err(error(String::from("facet ended without 'endloop'")));
+                }
+                open_facet = None;
+            }
+            ["outer", "loop"] | ["solid", ..] | ["endsolid", ..] | [] => {}
+            _ => return Err(error(format!("unexpected statement '{}'", line.trim()))),
+        }
+    }
+    if let Some(line) = open_facet {
+        return Err(MeshError::Parse { file: file.to_string(), line, message: String::from("facet is not terminated") });
+    }
+    Ok(builder.data)
+}
+
+struct MeshBuilder {
+    data: MeshData,
+    indices: HashMap<[u64; 3], usize>,
+}
+
+impl MeshBuilder {
+    fn new() -> MeshBuilder {
+        MeshBuilder { data: MeshData::new(), indices: HashMap::new() }
+    }
+
+    fn vertex(&mut self, p: Point3) -> usize {
+        // Adding zero folds -0.0 into 0.0 so both weld to the same vertex.
+        let key = [(p.x + 0.0).to_bits(), (p.y + 0.0).to_bits(), (p.z + 0.0).to_bits()];
+        let positions = &mut self.data.positions;
+        *self.indices.entry(key).or_insert_with(|| {
+            positions.push(p);
+            positions.len() - 1
+        })
+    }
+
+    fn add_triangle(&mut self, vertices: [Point3; 3]) {
+        let positions = vertices.map(|p| self.vertex(p));
+        self.data.faces.push(MeshFace { positions, normals: None, uvs: None });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::stl::parse_stl;
+
+    const ASCII: &str = "solid part\n\
+        facet normal 0 0 1\n outer loop\n  vertex 0 0 0\n  vertex 1 0 0\n  vertex 1 1 0\n endloop\nendfacet\n\
+        facet normal 0 0 1\n outer loop\n  vertex 0 0 0\n  vertex 1 1 0\n  vertex 0 1 0\n endloop\nendfacet\n\
+        endsolid part\n";
+
+    #[test]
+    fn check_parse_ascii_welds_vertices() {
+        let data = parse_stl(ASCII.as_bytes(), "part.stl").unwrap();
+        assert_eq!(data.faces.len(), 2);
+        assert_eq!(data.positions.len(), 4);
+        assert_eq!(data.faces[1].positions, [0, 2, 3]);
+    }
+
+    #[test]
+    fn check_parse_binary() {
+        let mut bytes = b"solid but actually binary".to_vec();
+        bytes.resize(80, 0);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        for value in [0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 2.0, 0.0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&[0, 0]);
+        let data = parse_stl(&bytes, "part.stl").unwrap();
+        assert_eq!(data.faces.len(), 1);
+        assert_approx_eq!(2.0, data.positions[2].y, 1e-9);
+    }
+
+    #[test]
+    fn check_malformed_ascii_reports_location() {
+        let source = ASCII.replace("vertex 1 1 0\n endloop\nendfacet\nfacet", "vertex 1 one 0\n endloop\nendfacet\nfacet");
+        let error = parse_stl(source.as_bytes(), "bad.stl").unwrap_err();
+        assert_eq!(format!("{}", error), "bad.stl:6: invalid number 'one'");
+        assert!(parse_stl(b"garbage", "bad.stl").is_err());
+
+        let cut = &ASCII[..ASCII.find("endloop\nendfacet\nendsolid").unwrap()];
+        let error = parse_stl(cut.as_bytes(), "cut.stl").unwrap_err();
+        assert_eq!(format!("{}", error), "cut.stl:9: facet is not terminated");
+
+        let mut header = vec![0; 80];
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_stl(&header, "huge.stl").is_err());
+    }
+}
@@ -0,0 +1,432 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::image::{Image, ImageError, load_image};
+use crate::perlin::Perlin;
+use crate::Vec3;
+use crate::vec::Point3;
+
+// Color varying over a surface, looked up by the hit's surface coordinates
+// (u, v) or by its position p.
+pub trait Texture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
+
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        self.color
+    }
+}
+
+// Checkerboard of cubes `size` wide filling space, so it looks the same on any
+// shape regardless of its UVs.
+pub struct Checker {
+    inv_size: f64,
+    even: Rc<dyn Texture>,
+    odd: Rc<dyn Texture>,
+}
+
+impl Checker {
+    pub fn new(size: f64, even: Rc<dyn Texture>, odd: Rc<dyn Texture>) -> Checker {
+        Checker { inv_size: 1.0 / size, even, odd }
+    }
+
+    pub fn from_colors(size: f64, even: Color, odd: Color) -> Checker {
+        Checker::new(size, Rc::new(SolidColor::new(even)), Rc::new(SolidColor::new(odd)))
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let cell = (p.x * self.inv_size).floor() + (p.y * self.inv_size).floor() + (p.z * self.inv_size).floor();
+        if cell.rem_euclid(2.0) == 0.0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+// Checkerboard with `columns` by `rows` squares over the unit UV square, so it
+// follows the surface parameterization.
+pub struct UvChecker {
+    columns: f64,
+    rows: f64,
+    even: Rc<dyn Texture>,
+    odd: Rc<dyn Texture>,
+}
+
+impl UvChecker {
+    pub fn new(columns: f64, rows: f64, even: Rc<dyn Texture>, odd: Rc<dyn Texture>) -> UvChecker {
+        UvChecker { columns, rows, even, odd }
+    }
+
+    pub fn from_colors(columns: f64, rows: f64, even: Color, odd: Color) -> UvChecker {
+        UvChecker::new(columns, rows, Rc::new(SolidColor::new(even)), Rc::new(SolidColor::new(odd)))
+    }
+}
+
+impl Texture for UvChecker {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let cell = (u * self.columns).floor() + (v * self.rows).floor();
+        if cell.rem_euclid(2.0) == 0.0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+// How image lookups outside the unit UV square behave.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WrapMode {
+    // Tile the image.
+    Repeat,
+    // Stretch the edge pixels outward.
+    Clamp,
+}
+
+// Image mapped over the unit UV square with (0, 0) at its bottom-left corner,
+// filtered bilinearly between the four nearest pixels.
+pub struct ImageTexture {
+    image: Image,
+    wrap: WrapMode,
+}
+
+impl ImageTexture {
+    // Image files store gamma-encoded colors; the renderer writes gamma 2, so
+    // pixels are squared back to linear light here.
+    pub fn new(image: &Image, wrap: WrapMode) -> ImageTexture {
+        let pixels = (0..image.height()).flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let c = image.pixel(x, y);
+                c * c
+            })
+            .collect();
+        ImageTexture { image: Image::new(image.width(), image.height(), pixels), wrap }
+    }
+
+    // Loads a PNG, PGM or PPM file.
+    pub fn load(path: &str, wrap: WrapMode) -> Result<ImageTexture, ImageError> {
+        Ok(ImageTexture::new(&load_image(path)?, wrap))
+    }
+
+    fn index(&self, i: f64, size: usize) -> usize {
+        match self.wrap {
+            WrapMode::Repeat => i.rem_euclid(size as f64) as usize % size,
+            WrapMode::Clamp => i.clamp(0.0, (size - 1) as f64) as usize,
+        }
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> Color {
+        let (width, height) = (self.image.width(), self.image.height());
+        if width == 0 || height == 0 {
+            // Cyan makes a missing image stand out.
+            return Color::new(0.0, 1.0, 1.0);
+        }
+        // Pixel centers sit at half-integer positions; rows run top to bottom.
+        let x = u * width as f64 - 0.5;
+        let y = (1.0 - v) * height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (i0, i1) = (self.index(x0, width), self.index(x0 + 1.0, width));
+        let (j0, j1) = (self.index(y0, height), self.index(y0 + 1.0, height));
+        let top = (1.0 - fx) * self.image.pixel(i0, j0) + fx * self.image.pixel(i1, j0);
+        let bottom = (1.0 - fx) * self.image.pixel(i0, j1) + fx * self.image.pixel(i1, j1);
+        (1.0 - fy) * top + fy * bottom
+    }
+}
+
+// Turbulence octaves used by the noise textures.
+const OCTAVES: usize = 7;
+
+pub(crate) fn lerp(a: Color, b: Color, t: f64) -> Color {
+    (1.0 - t) * a + t * b
+}
+
+// Stone with veins: sine bands along z, bent by turbulence. `scale` sets how
+// many bands fit in a unit of distance.
+pub struct Marble {
+    noise: Perlin,
+    scale: f64,
+    base: Color,
+    vein: Color,
+}
+
+impl Marble {
+    pub fn new(scale: f64, base: Color, vein: Color, seed: u64) -> Marble {
+        Marble { noise: Perlin::new(seed), scale, base, vein }
+    }
+}
+
+impl Texture for Marble {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let phase = self.scale * p.z + 10.0 * self.noise.turbulence(p, OCTAVES);
+        lerp(self.vein, self.base, 0.5 * (1.0 + phase.sin()))
+    }
+}
+
+// Growth rings around the y axis, `rings` per unit of radius, wobbled by noise.
+pub struct Wood {
+    noise: Perlin,
+    rings: f64,
+    light: Color,
+    dark: Color,
+}
+
+impl Wood {
+    pub fn new(rings: f64, light: Color, dark: Color, seed: u64) -> Wood {
+        Wood { noise: Perlin::new(seed), rings, light, dark }
+    }
+}
+
+impl Texture for Wood {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let radius = (p.x * p.x + p.z * p.z).sqrt() + 0.1 * self.noise.fbm(&(*p * 2.0), OCTAVES);
+        let ring = (radius * self.rings).rem_euclid(1.0);
+        // Sharpen each ring into a thin dark band of latewood.
+        lerp(self.light, self.dark, ring * ring * ring)
+    }
+}
+
+// Soft cloud cover from fBm noise; `coverage` in [0, 1] is roughly the share of
+// the sky with any cloud in it.
+pub struct Clouds {
+    noise: Perlin,
+    scale: f64,
+    coverage: f64,
+    sky: Color,
+    cloud: Color,
+}
+
+impl Clouds {
+    pub fn new(scale: f64, coverage: f64, sky: Color, cloud: Color, seed: u64) -> Clouds {
+        Clouds { noise: Perlin::new(seed), scale, coverage, sky, cloud }
+    }
+}
+
+impl Texture for Clouds {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let density = self.noise.fbm(&(*p * self.scale), OCTAVES) + 2.0 * self.coverage - 1.0;
+        lerp(self.sky, self.cloud, density.clamp(0.0, 1.0))
+    }
+}
+
+// Samples `texture` with its coordinates multiplied by `factor`, so the pattern
+// repeats `factor` times as often.
+pub struct Scaled {
+    texture: Rc<dyn Texture>,
+    factor: f64,
+}
+
+impl Scaled {
+    pub fn new(texture: Rc<dyn Texture>, factor: f64) -> Scaled {
+        Scaled { texture, factor }
+    }
+}
+
+impl Texture for Scaled {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        self.texture.value(u * self.factor, v * self.factor, &(*p * self.factor))
+    }
+}
+
+// Samples `texture` shifted by `offset`: the whole vector moves the solid
+// coordinates and its x and y move u and v.
+pub struct Offset {
+    texture: Rc<dyn Texture>,
+    offset: Vec3,
+}
+
+impl Offset {
+    pub fn new(texture: Rc<dyn Texture>, offset: Vec3) -> Offset {
+        Offset { texture, offset }
+    }
+}
+
+impl Texture for Offset {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        self.texture.value(u + self.offset.x, v + self.offset.y, &(*p + self.offset))
+    }
+}
+
+// Blends `a` into `b` channel by channel, by the value of `factor`
+// (0 gives `a`, 1 gives `b`).
+pub struct Mix {
+    a: Rc<dyn Texture>,
+    b: Rc<dyn Texture>,
+    factor: Rc<dyn Texture>,
+}
+
+impl Mix {
+    pub fn new(a: Rc<dyn Texture>, b: Rc<dyn Texture>, factor: Rc<dyn Texture>) -> Mix {
+        Mix { a, b, factor }
+    }
+}
+
+impl Texture for Mix {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let t = self.factor.value(u, v, p);
+        (Color::new(1.0, 1.0, 1.0) - t) * self.a.value(u, v, p) + t * self.b.value(u, v, p)
+    }
+}
+
+// Maps each channel of `texture` linearly from [low, high] onto [from, to],
+// clamping outside the range. Turns a grey pattern into a color ramp, or into a
+// hard step at `low` when `high` equals it.
+pub struct Remap {
+    texture: Rc<dyn Texture>,
+    low: f64,
+    high: f64,
+    from: Color,
+    to: Color,
+}
+
+impl Remap {
+    pub fn new(texture: Rc<dyn Texture>, low: f64, high: f64, from: Color, to: Color) -> Remap {
+        Remap { texture, low, high, from, to }
+    }
+}
+
+impl Texture for Remap {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let c = self.texture.value(u, v, p);
+        let t = |x: f64| {
+            if self.high == self.low {
+                if x < self.low { 0.0 } else { 1.0 }
+            } else {
+                ((x - self.low) / (self.high - self.low)).clamp(0.0, 1.0)
+            }
+        };
+        let t = Color::new(t(c.x), t(c.y), t(c.z));
+        self.from + t * (self.to - self.from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::image::Image;
+    use crate::texture::{Checker, Clouds, ImageTexture, Marble, Mix, Offset, Remap, Scaled, SolidColor, Texture, UvChecker, Wood, WrapMode};
+    use crate::vec::{Point3, Vec3};
+
+    #[test]
+    fn check_solid_checker() {
+        let checker = Checker::from_colors(2.0, Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0));
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        assert_eq!(format!("{}", checker.value(0.0, 0.0, &Point3::new(0.5, 1.5, 1.9))), "1 1 1");
+        assert_eq!(format!("{}", checker.value(0.0, 0.0, &Point3::new(2.5, 1.5, 1.9))), "0 0 0");
+        // Negative coordinates continue the pattern instead of mirroring it.
+        assert_eq!(format!("{}", checker.value(0.0, 0.0, &Point3::new(-0.5, 0.0, 0.0))), "0 0 0");
+        assert_eq!(format!("{}", checker.value(0.0, 0.0, &Point3::new(-2.5, 0.0, 0.0))), "1 1 1");
+        assert_eq!(format!("{}", checker.value(0.7, 0.2, &origin)), "1 1 1");
+    }
+
+    #[test]
+    fn check_uv_checker() {
+        let checker = UvChecker::from_colors(4.0, 2.0, Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0));
+        let p = Point3::new(0.0, 0.0, 0.0);
+        assert_eq!(format!("{}", checker.value(0.1, 0.1, &p)), "1 1 1");
+        assert_eq!(format!("{}", checker.value(0.3, 0.1, &p)), "0 0 0");
+        assert_eq!(format!("{}", checker.value(0.3, 0.6, &p)), "1 1 1");
+    }
+
+    // Black and white columns over a red row on top.
+    fn stripes() -> Image {
+        Image::new(2, 2, vec![Color::new(1.0, 0.0, 0.0), Color::new(1.0, 0.0, 0.0),
+                              Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)])
+    }
+
+    #[test]
+    fn check_image_texture_filtering() {
+        let texture = ImageTexture::new(&stripes(), WrapMode::Clamp);
+        let p = Point3::new(0.0, 0.0, 0.0);
+        // Pixel centers give the pixels themselves.
+        assert_eq!(format!("{}", texture.value(0.25, 0.75, &p)), "1 0 0");
+        assert_eq!(format!("{}", texture.value(0.75, 0.25, &p)), "1 1 1");
+        // Half way between the bottom pixels.
+        assert_eq!(format!("{}", texture.value(0.5, 0.25, &p)), "0.5 0.5 0.5");
+        // Clamped past the right edge.
+        assert_eq!(format!("{}", texture.value(1.5, 0.25, &p)), "1 1 1");
+    }
+
+    #[test]
+    fn check_image_texture_wrap() {
+        let texture = ImageTexture::new(&stripes(), WrapMode::Repeat);
+        let p = Point3::new(0.0, 0.0, 0.0);
+        assert_eq!(format!("{}", texture.value(1.25, 0.25, &p)), "0 0 0");
+        assert_eq!(format!("{}", texture.value(-0.25, 0.25, &p)), "1 1 1");
+        // At the left edge the filter blends in the wrapped right column.
+        assert_approx_eq!(0.5, texture.value(0.0, 0.25, &p).x, 1e-12);
+
+        // Stored colors are gamma 2, so mid grey comes back as a quarter.
+        let grey = ImageTexture::new(&Image::new(1, 1, vec![Color::new(0.5, 0.5, 0.5)]), WrapMode::Clamp);
+        assert_eq!(format!("{}", grey.value(0.3, 0.3, &p)), "0.25 0.25 0.25");
+    }
+
+    #[test]
+    fn check_noise_textures_are_reproducible() {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let black = Color::new(0.0, 0.0, 0.0);
+        let p = Point3::new(0.3, 1.7, -0.8);
+        let textures: [(Box<dyn Texture>, Box<dyn Texture>); 3] = [
+            (Box::new(Marble::new(4.0, white, black, 11)), Box::new(Marble::new(4.0, white, black, 11))),
+            (Box::new(Wood::new(6.0, white, black, 11)), Box::new(Wood::new(6.0, white, black, 11))),
+            (Box::new(Clouds::new(2.0, 0.5, black, white, 11)), Box::new(Clouds::new(2.0, 0.5, black, white, 11))),
+        ];
+        for (a, b) in &textures {
+            let color = a.value(0.0, 0.0, &p);
+            assert_eq!(format!("{}", color), format!("{}", b.value(0.0, 0.0, &p)));
+            assert!((0..3).all(|i| (0.0..=1.0).contains(&color[i])));
+        }
+    }
+
+    #[test]
+    fn check_cloud_coverage() {
+        let sky = Color::new(0.0, 0.0, 1.0);
+        let cloud = Color::new(1.0, 1.0, 1.0);
+        let p = Point3::new(0.3, 1.7, -0.8);
+        assert_eq!(format!("{}", Clouds::new(2.0, 0.0, sky, cloud, 5).value(0.0, 0.0, &p)), "0 0 1");
+        // Full coverage leaves no clear sky, though thin spots still show some blue.
+        assert!(Clouds::new(2.0, 1.0, sky, cloud, 5).value(0.0, 0.0, &p).x > 0.5);
+    }
+
+    #[test]
+    fn check_combinators() {
+        let p = Point3::new(0.0, 0.0, 0.0);
+        let checker = Rc::new(UvChecker::from_colors(2.0, 2.0, Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0)));
+        // Twice as fine: u = 0.3 lands where 0.6 used to be.
+        let fine = Scaled::new(checker.clone(), 2.0);
+        assert_eq!(format!("{}", fine.value(0.3, 0.1, &p)), "0 0 0");
+        let shifted = Offset::new(checker.clone(), Vec3::new(0.5, 0.0, 0.0));
+        assert_eq!(format!("{}", shifted.value(0.1, 0.1, &p)), "0 0 0");
+
+        let red = Rc::new(SolidColor::new(Color::new(1.0, 0.0, 0.0)));
+        let blue = Rc::new(SolidColor::new(Color::new(0.0, 0.0, 1.0)));
+        let quarter = Rc::new(SolidColor::new(Color::new(0.25, 0.25, 0.25)));
+        assert_eq!(format!("{}", Mix::new(red.clone(), blue.clone(), quarter.clone()).value(0.0, 0.0, &p)), "0.75 0 0.25");
+        // A black and white checker as the factor picks one input or the other.
+        assert_eq!(format!("{}", Mix::new(red, blue, checker).value(0.6, 0.1, &p)), "1 0 0");
+
+        let ramp = Remap::new(quarter.clone(), 0.0, 0.5, Color::new(0.0, 0.0, 0.0), Color::new(0.0, 1.0, 2.0));
+        assert_eq!(format!("{}", ramp.value(0.0, 0.0, &p)), "0 0.5 1");
+        let step = Remap::new(quarter, 0.25, 0.25, Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(format!("{}", step.value(0.0, 0.0, &p)), "1 1 1");
+    }
+}
@@ -0,0 +1,138 @@
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::Aabb;
+use crate::frame::{AxisFrame, SurfaceHit};
+use crate::material::Scatter;
+use crate::plane::disk_box;
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::roots::solve_quartic;
+use crate::vec::Point3;
+
+// Ring around `axis` through `center`: the set of points at distance
+// `minor_radius` from the circle of `major_radius` in the plane normal to the axis.
+pub struct Torus {
+    frame: AxisFrame,
+    major_radius: f64,
+    minor_radius: f64,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Torus {
+    pub fn new(center: Point3, axis: Vec3, major_radius: f64, minor_radius: f64, mat_ptr: Rc<dyn Scatter>) -> Torus {
+        Torus { frame: AxisFrame::new(center, center + axis), major_radius, minor_radius, mat_ptr }
+    }
+
+    fn hit_local(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<SurfaceHit> {
+        let (o, d) = self.frame.local_ray(r);
+        let length = d.length();
+        let d = d / length;
+        // Restart the ray at its closest approach to the center so the quartic
+        // coefficients stay small for distant origins.
+        let shift = -vec::dot(&o, &d);
+        let o = o + shift * d;
+
+        let r2 = self.major_radius * self.major_radius;
+        let f = vec::dot(&o, &d);
+        let k = o.length_squared() + r2 - self.minor_radius * self.minor_radius;
+        // (|p|² + R² - r²)² = 4R²(px² + py²) with p = o + s d and |d| = 1.
+        let roots = solve_quartic(1.0,
+                                  4.0 * f,
+                                  4.0 * f * f + 2.0 * k - 4.0 * r2 * (1.0 - d.z * d.z),
+                                  4.0 * f * k - 8.0 * r2 * (f - o.z * d.z),
+                                  k * k - 4.0 * r2 * (o.length_squared() - o.z * o.z));
+
+        for s in roots {
+            let t = (s + shift) / length;
+            if t < t_min || t > t_max {
+                continue;
+            }
+            let p = o + s * d;
+            let ring_distance = (p.x * p.x + p.y * p.y).sqrt();
+            if ring_distance == 0.0 {
+                continue;
+            }
+            // The normal points away from the nearest point on the center circle.
+            let ring_point = Vec3::new(p.x, p.y, 0.0) * (self.major_radius / ring_distance);
+            let normal = p - ring_point;
+            let u = (f64::atan2(p.y, p.x) + PI) / (2.0 * PI);
+            let v = (f64::atan2(p.z, ring_distance - self.major_radius) + PI) / (2.0 * PI);
+            return Some(SurfaceHit { t, normal, u, v });
+        }
+        None
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let hit = self.hit_local(r, t_min, t_max)?;
+        Some(self.frame.record(r, hit, &self.mat_ptr))
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        let ring = disk_box(self.frame.base, self.frame.axis, self.major_radius);
+        let tube = Vec3::new(self.minor_radius, self.minor_radius, self.minor_radius);
+        Some(Aabb::new(ring.min() - tube, ring.max() + tube))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, Ray};
+    use crate::torus::Torus;
+    use crate::vec::{Point3, Vec3};
+
+    // Ring of radius 2 in the xz plane with a tube of radius 0.5.
+    fn ring() -> Torus {
+        Torus::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 2.0, 0.5,
+                   Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_hit_through_ring() {
+        let r = Ray::new(Point3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = ring().hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(7.5, rec.t, 1e-9);
+        assert_approx_eq!(-1.0, rec.normal.x, 1e-9);
+        assert!(rec.front_face);
+
+        // Past the first tube the ray crosses the hole and enters the far tube at x = 1.5.
+        let rec = ring().hit(&r, 9.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(11.5, rec.t, 1e-9);
+        assert_approx_eq!(-1.0, rec.normal.x, 1e-9);
+    }
+
+    #[test]
+    fn check_hit_from_above_and_through_hole() {
+        let r = Ray::new(Point3::new(2.0, 5.0, 0.0), Vec3::new(0.0, -2.0, 0.0));
+        let rec = ring().hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(2.25, rec.t, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.y, 1e-9);
+
+        let hole = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(ring().hit(&hole, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn check_hit_from_inside_tube() {
+        let r = Ray::new(Point3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = ring().hit(&r, 0.0001, f64::INFINITY).unwrap();
+        assert!(!rec.front_face);
+        assert_approx_eq!(1.5, rec.t, 1e-9);
+    }
+
+    #[test]
+    fn check_bounding_box() {
+        let bbox = ring().bounding_box(0.0, 1.0).unwrap();
+        assert_approx_eq!(-2.5, bbox.min().x, 1e-9);
+        assert_approx_eq!(0.5, bbox.max().y, 1e-9);
+        assert_approx_eq!(2.5, bbox.max().z, 1e-9);
+    }
+}
@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use crate::vec;
+use crate::aabb::Aabb;
+use crate::matrix::Matrix4;
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::vec::Point3;
+
+// Places any object in the world through an affine matrix. Rays are moved into
+// object space instead of moving the geometry; the direction is not normalized,
+// so `t` means the same thing in both spaces.
+pub struct Transform {
+    object: Rc<dyn Hittable>,
+    object_to_world: Matrix4,
+    world_to_object: Matrix4,
+    // Normals transform by the inverse transpose to stay perpendicular under non-uniform scale.
+    normal_to_world: Matrix4,
+}
+
+impl Transform {
+    pub fn new(object: Rc<dyn Hittable>, object_to_world: Matrix4) -> Transform {
+        let world_to_object = object_to_world.inverse().expect("Transform matrix is not invertible.");
+        Transform { object, object_to_world, world_to_object, normal_to_world: world_to_object.transpose() }
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let object_ray = Ray::with_time(self.world_to_object.transform_point(r.origin()),
+                                        self.world_to_object.transform_vector(r.direction()),
+                                        r.time());
+        let mut rec = self.object.hit(&object_ray, t_min, t_max)?;
+        // The sign of normal · direction survives the transform, so front_face
+        // and the orientation of the normal stay valid.
+        rec.p = self.object_to_world.transform_point(rec.p);
+        rec.normal = vec::unit_vector(self.normal_to_world.transform_vector(rec.normal));
+        Some(rec)
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Aabb> {
+        let bbox = self.object.bounding_box(time0, time1)?;
+        let (min, max) = (bbox.min(), bbox.max());
+        let mut corners = [Point3::default(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let p = Point3::new(if i & 1 == 0 { min.x } else { max.x },
+                                if i & 2 == 0 { min.y } else { max.y },
+                                if i & 4 == 0 { min.z } else { max.z });
+            *corner = self.object_to_world.transform_point(p);
+        }
+        Some(Aabb::from_points(&corners))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::cuboid::Cuboid;
+    use crate::material::Lambertian;
+    use crate::matrix::Matrix4;
+    use crate::raytracing::{Hittable, Ray, Sphere};
+    use crate::transform::Transform;
+    use crate::vec::{Point3, Vec3};
+
+    fn unit_sphere() -> Rc<Sphere> {
+        Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))))
+    }
+
+    #[test]
+    fn check_translate() {
+        let moved = Transform::new(unit_sphere(), Matrix4::translation(Vec3::new(0.0, 0.0, -5.0)));
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = moved.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(4.0, rec.t, 1e-9);
+        assert_approx_eq!(-4.0, rec.p.z, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.z, 1e-9);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn check_non_uniform_scale_normal() {
+        // Ellipsoid with semi-axes 2, 1, 1; at (sqrt(2), sqrt(0.5), 0) its normal is along (1, 2, 0).
+        let ellipsoid = Transform::new(unit_sphere(), Matrix4::scaling(Vec3::new(2.0, 1.0, 1.0)));
+        let target = Point3::new(2.0_f64.sqrt(), 0.5_f64.sqrt(), 0.0);
+        let n = Vec3::new(1.0, 2.0, 0.0) / 5.0_f64.sqrt();
+        let r = Ray::new(target + 5.0 * n, -n);
+        let rec = ellipsoid.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(5.0, rec.t, 1e-9);
+        assert_approx_eq!(1.0 / 5.0_f64.sqrt(), rec.normal.x, 1e-9);
+        assert_approx_eq!(2.0 / 5.0_f64.sqrt(), rec.normal.y, 1e-9);
+    }
+
+    #[test]
+    fn check_rotated_bounding_box() {
+        let cuboid = Rc::new(Cuboid::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0),
+                                         Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))));
+        let rotated = Transform::new(cuboid, Matrix4::rotation_y(90.0));
+        let bbox = rotated.bounding_box(0.0, 1.0).unwrap();
+        assert_approx_eq!(-2.0, bbox.min().z, 1e-9);
+        assert_approx_eq!(1.0, bbox.max().x, 1e-9);
+
+        let r = Ray::new(Point3::new(0.5, 0.5, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = rotated.hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(5.0, rec.t, 1e-9);
+        assert_approx_eq!(1.0, rec.normal.z, 1e-9);
+    }
+}
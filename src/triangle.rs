@@ -0,0 +1,119 @@
+use std::rc::Rc;
+
+use crate::{vec, Vec3};
+use crate::aabb::Aabb;
+use crate::material::Scatter;
+use crate::raytracing::{HitRecord, Hittable, Ray};
+use crate::vec::Point3;
+
+pub struct Triangle {
+    v0: Point3,
+    v1: Point3,
+    v2: Point3,
+    mat_ptr: Rc<dyn Scatter>,
+}
+
+impl Triangle {
+    // Vertices are expected in counter-clockwise order when seen from the outside,
+    // which makes the geometric normal point outwards.
+    pub fn new(v0: Point3, v1: Point3, v2: Point3, mat_ptr: Rc<dyn Scatter>) -> Triangle {
+        Triangle { v0, v1, v2, mat_ptr }
+    }
+}
+
+// Möller–Trumbore ray/triangle intersection. Returns the ray parameter together
+// with the barycentric coordinates (b1, b2) of the hit point with respect to v1 and v2.
+pub fn intersect(r: &Ray, v0: Point3, v1: Point3, v2: Point3, t_min: f64, t_max: f64) -> Option<(f64, f64, f64)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let p_vec = vec::cross(&r.direction(), &edge2);
+    let det = vec::dot(&edge1, &p_vec);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let t_vec = r.origin() - v0;
+    let b1 = vec::dot(&t_vec, &p_vec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+    let q_vec = vec::cross(&t_vec, &edge1);
+    let b2 = vec::dot(&r.direction(), &q_vec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+    let t = vec::dot(&edge2, &q_vec) * inv_det;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    Some((t, b1, b2))
+}
+
+pub fn triangle_box(v0: Point3, v1: Point3, v2: Point3) -> Aabb {
+    let small = Point3::new(v0.x.min(v1.x).min(v2.x), v0.y.min(v1.y).min(v2.y), v0.z.min(v1.z).min(v2.z));
+    let big = Point3::new(v0.x.max(v1.x).max(v2.x), v0.y.max(v1.y).max(v2.y), v0.z.max(v1.z).max(v2.z));
+    Aabb::new(small, big).pad(0.0001)
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t, b1, b2) = intersect(r, self.v0, self.v1, self.v2, t_min, t_max)?;
+        let outward_normal: Vec3 = vec::unit_vector(vec::cross(&(self.v1 - self.v0), &(self.v2 - self.v0)));
+        let mut rec = HitRecord::new(r.at(t), outward_normal, Rc::clone(&self.mat_ptr), t);
+        rec.u = b1;
+        rec.v = b2;
+        rec.set_face_normal(r);
+        Some(rec)
+    }
+
+    fn bounding_box(&self, _: f64, _: f64) -> Option<Aabb> {
+        Some(triangle_box(self.v0, self.v1, self.v2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::raytracing::{Hittable, Ray};
+    use crate::triangle::Triangle;
+    use crate::vec::{Point3, Vec3};
+
+    fn triangle() -> Triangle {
+        Triangle::new(Point3::new(0.0, 0.0, 0.0),
+                      Point3::new(1.0, 0.0, 0.0),
+                      Point3::new(0.0, 1.0, 0.0),
+                      Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    #[test]
+    fn check_hit_front_face() {
+        let r = Ray::new(Point3::new(0.25, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = triangle().hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert_approx_eq!(2.0, rec.t, 1e-9);
+        assert_approx_eq!(0.25, rec.u, 1e-9);
+        assert_approx_eq!(0.5, rec.v, 1e-9);
+        assert!(rec.front_face);
+        assert_approx_eq!(1.0, rec.normal.z, 1e-9);
+    }
+
+    #[test]
+    fn check_hit_back_face() {
+        let r = Ray::new(Point3::new(0.25, 0.25, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = triangle().hit(&r, 0.0, f64::INFINITY).unwrap();
+        assert!(!rec.front_face);
+        assert_approx_eq!(-1.0, rec.normal.z, 1e-9);
+    }
+
+    #[test]
+    fn check_miss() {
+        let outside = Ray::new(Point3::new(0.75, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(triangle().hit(&outside, 0.0, f64::INFINITY).is_none());
+        let parallel = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(triangle().hit(&parallel, 0.0, f64::INFINITY).is_none());
+    }
+}
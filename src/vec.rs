@@ -1,5 +1,5 @@
 use std::fmt::{Display, Formatter};
-use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::ops::{Add, Div, Index, Mul, Neg, Sub};
 
 use crate::raytracing::{random_double, random_double_range};
 
@@ -71,6 +71,18 @@ pub fn unit_vector(vec: Vec3) -> Vec3 {
     vec / vec.length()
 }
 
+// Two unit vectors that complete the unit vector `normal` to an orthonormal basis.
+pub fn tangent_frame(normal: Vec3) -> (Vec3, Vec3) {
+    let a = if normal.x.abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = unit_vector(cross(&normal, &a));
+    let bitangent = cross(&normal, &tangent);
+    (tangent, bitangent)
+}
+
 pub fn random_in_unit_disk() -> Vec3 {
     loop {
         let p = Vec3::new(random_double_range(-1.0, 1.0),
@@ -163,6 +175,19 @@ impl Add<Vec3> for Vec3 {
     }
 }
 
+impl Index<usize> for Vec3 {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 index out of range: {}", index),
+        }
+    }
+}
+
 impl Neg for Vec3 {
     type Output = Vec3;
 
@@ -233,6 +258,14 @@ mod tests {
         assert_approx_eq!(8.4, f, 1e-4);
     }
 
+    #[test]
+    fn check_index() {
+        let vec3 = Vec3::new(1.0, 2.0, 3.0);
+        assert_approx_eq!(1.0, vec3[0], 1e-4);
+        assert_approx_eq!(2.0, vec3[1], 1e-4);
+        assert_approx_eq!(3.0, vec3[2], 1e-4);
+    }
+
     #[test]
     fn check_unit_vector() {
         let vec3 = Vec3::new(1.0, 2.0, 2.0);